@@ -0,0 +1,12 @@
+#version 330 core
+
+in vec2 textureCoordinates;
+in vec4 color;
+out vec4 fragmentColor;
+
+uniform sampler2D batchTexture;
+
+void main()
+{
+    fragmentColor = color * texture(batchTexture, textureCoordinates);
+}
@@ -0,0 +1,16 @@
+#version 330 core
+
+layout (location = 0) in vec2 vertexPosition;
+layout (location = 1) in vec2 textureCoordinate;
+layout (location = 2) in vec4 vertexColor;
+
+out vec2 textureCoordinates;
+out vec4 color;
+
+uniform mat4 viewProjection;
+
+void main() {
+    textureCoordinates = textureCoordinate;
+    color = vertexColor;
+    gl_Position = viewProjection * vec4(vertexPosition, 0.0, 1.0);
+}
@@ -0,0 +1,9 @@
+#version 330 core
+
+in vec4 color;
+out vec4 fragmentColor;
+
+void main()
+{
+    fragmentColor = color;
+}
@@ -0,0 +1,13 @@
+#version 330 core
+
+layout (location = 0) in vec2 vertexPosition;
+layout (location = 1) in vec4 vertexColor;
+
+out vec4 color;
+
+uniform mat4 viewProjection;
+
+void main() {
+    color = vertexColor;
+    gl_Position = viewProjection * vec4(vertexPosition, 0.0, 1.0);
+}
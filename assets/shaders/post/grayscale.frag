@@ -0,0 +1,13 @@
+#version 330 core
+
+in vec2 textureCoordinates;
+out vec4 fragmentColor;
+
+uniform sampler2D screenTexture;
+
+void main()
+{
+    vec4 color = texture(screenTexture, textureCoordinates);
+    float luminance = dot(color.rgb, vec3(0.2126, 0.7152, 0.0722));
+    fragmentColor = vec4(vec3(luminance), color.a);
+}
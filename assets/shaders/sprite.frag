@@ -4,9 +4,9 @@ in vec2 textureCoordinates;
 out vec4 color;
 
 uniform sampler2D spriteTexture;
-uniform vec3 spriteColor;
+uniform vec4 spriteColor;
 
 void main()
 {
-    color = vec4(spriteColor, 1.0) * texture(spriteTexture, textureCoordinates);
+    color = spriteColor * texture(spriteTexture, textureCoordinates);
 }
\ No newline at end of file
@@ -6,8 +6,10 @@ layout (location = 1) in vec2 textureCoordinate;
 out vec2 textureCoordinates;
 
 uniform mat4 transform;
+// The normalized texture offset (xy) and scale (zw) to sample from.
+uniform vec4 textureRegion;
 
 void main() {
-    textureCoordinates = textureCoordinate;
+    textureCoordinates = textureRegion.xy + textureCoordinate * textureRegion.zw;
     gl_Position = transform * vec4(vertexPosition, 0.0, 1.0);
 }
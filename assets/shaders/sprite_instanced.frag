@@ -0,0 +1,12 @@
+#version 330 core
+
+in vec2 textureCoordinates;
+in vec4 spriteColor;
+out vec4 color;
+
+uniform sampler2D spriteTexture;
+
+void main()
+{
+    color = spriteColor * texture(spriteTexture, textureCoordinates);
+}
\ No newline at end of file
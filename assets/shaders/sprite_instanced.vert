@@ -0,0 +1,17 @@
+#version 330 core 
+
+layout (location = 0) in vec2 vertexPosition;
+layout (location = 1) in vec2 textureCoordinate;
+layout (location = 2) in mat4 instanceModel;
+layout (location = 6) in vec4 instanceColor;
+
+out vec2 textureCoordinates;
+out vec4 spriteColor;
+
+uniform mat4 viewProjection;
+
+void main() {
+    textureCoordinates = textureCoordinate;
+    spriteColor = instanceColor;
+    gl_Position = viewProjection * instanceModel * vec4(vertexPosition, 0.0, 1.0);
+}
@@ -0,0 +1,63 @@
+use babo::{prelude::*, PostProcess};
+
+fn main() {
+    let mut window = Window::new(1280, 720, "Babo Engine: Post Processing").unwrap();
+
+    let mut camera = Camera::from(&window);
+
+    let sprite_renderer = SpriteRenderer::new().unwrap();
+
+    let texture = Texture::from_file("./assets/textures/babo.png").unwrap();
+
+    // Render the scene offscreen and draw it back in grayscale.
+    let (width, height) = window.drawable_size();
+    let mut post_process = PostProcess::new(width, height).unwrap();
+    post_process.add_pass(PostProcess::grayscale().unwrap());
+
+    let mut rotation = 0.0;
+
+    loop {
+        if !window.running() {
+            break;
+        }
+
+        for event in window.events() {
+            match event {
+                Event::Quit => {
+                    window.stop();
+                }
+                Event::KeyDown {
+                    key: Key::Escape, ..
+                } => {
+                    window.stop();
+                }
+                _ => {}
+            }
+        }
+
+        // Keep the camera and offscreen targets matching the window.
+        if window.resized() {
+            let (width, height) = window.drawable_size();
+            camera.set_screen(width as f32, height as f32);
+            post_process.resize(width, height).unwrap();
+        }
+
+        rotation += 0.6 * window.delta_time();
+
+        post_process
+            .render(|| {
+                window.clear(0.25, 0.5, 0.75);
+
+                sprite_renderer.draw_params(
+                    &texture,
+                    camera.projection(),
+                    camera.view(),
+                    &DrawParams::new().rotation(rotation),
+                )
+            })
+            .unwrap()
+            .unwrap();
+
+        window.present();
+    }
+}
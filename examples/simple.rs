@@ -1,9 +1,4 @@
-use nalgebra::{Vector2, Vector3};
-use sdl2::{event::Event, keyboard::Keycode};
-
-use babo::{
-    Camera, SpriteRenderer, SpriteRendererTrait, Texture, TextureTrait, Window, WindowTrait,
-};
+use babo::prelude::*;
 
 fn main() {
     let mut window = Window::new(1280, 720, "Babo Engine: v0.0.1").unwrap();
@@ -16,10 +11,10 @@ fn main() {
 
     let babo_texture = Texture::from_file("./assets/textures/babo.png").unwrap();
 
-    let mut position = Vector3::new(0.0, 0.0, 1.0);
+    let mut position = Vec3::new(0.0, 0.0, 1.0);
     let mut rotation = 0.0;
 
-    let big_boy_position = Vector3::new(128.0, 0.0, 1.0);
+    let big_boy_position = Vec3::new(128.0, 0.0, 1.0);
     let mut big_boy_rotation = 0.0;
 
     // Handle events.
@@ -30,12 +25,11 @@ fn main() {
 
         for event in window.events() {
             match event {
-                Event::Quit { .. } => {
+                Event::Quit => {
                     window.stop();
                 }
                 Event::KeyDown {
-                    keycode: Some(Keycode::Escape),
-                    ..
+                    key: Key::Escape, ..
                 } => {
                     window.stop();
                 }
@@ -43,16 +37,23 @@ fn main() {
             }
         }
 
-        // Update some variables.
-        position.x += 2.0;
-        position.y += 0.4;
-        rotation += 0.01;
-        big_boy_rotation -= 0.005;
+        // Keep the camera projection matching the window.
+        if window.resized() {
+            let (width, height) = window.drawable_size();
+            camera.set_screen(width as f32, height as f32);
+        }
+
+        // Update some variables, scaled by the frame time to be framerate independent.
+        let dt = window.delta_time();
+        position.x += 120.0 * dt;
+        position.y += 24.0 * dt;
+        rotation += 0.6 * dt;
+        big_boy_rotation -= 0.3 * dt;
 
         // Set the camera at the center of sprite.
         camera.set_position(
             position.xy()
-                + Vector2::new(
+                + Vec2::new(
                     babo_texture.width() as f32 / 2.0,
                     babo_texture.height() as f32 / 2.0,
                 ),
@@ -63,27 +64,22 @@ fn main() {
 
         // Render the sprite with the camera.
         sprite_renderer
-            .draw(
+            .draw_combined(
                 &texture,
-                &camera.projection(),
-                &camera.view(),
-                &big_boy_position,
-                &Vector2::new(720.0, 720.0),
-                big_boy_rotation,
-                &Vector3::new(1.0, 1.0, 1.0),
+                camera.view_projection(),
+                &DrawParams::new()
+                    .position(big_boy_position)
+                    .size(Vec2::new(720.0, 720.0))
+                    .rotation(big_boy_rotation),
             )
             .unwrap();
 
         // Render the big boy sprite with the camera.
         sprite_renderer
-            .draw(
+            .draw_combined(
                 &babo_texture,
-                &camera.projection(),
-                &camera.view(),
-                &position,
-                &Vector2::new(babo_texture.width() as f32, babo_texture.height() as f32),
-                rotation,
-                &Vector3::new(1.0, 1.0, 1.0),
+                camera.view_projection(),
+                &DrawParams::new().position(position).rotation(rotation),
             )
             .unwrap();
 
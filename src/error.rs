@@ -0,0 +1,38 @@
+use snafu::Snafu;
+
+#[cfg(feature = "opengl")]
+use crate::rendering::ShaderError;
+#[cfg(feature = "sdl2")]
+use crate::rendering::WindowError;
+use crate::utils::GlError;
+
+/// Any error returned by babo.
+#[derive(Debug, Snafu)]
+pub enum Error {
+    #[snafu(context(false), display("{}", source))]
+    Gl { source: GlError },
+    #[cfg(feature = "opengl")]
+    #[snafu(context(false), display("{}", source))]
+    Shader { source: ShaderError },
+    #[cfg(feature = "sdl2")]
+    #[snafu(context(false), display("{}", source))]
+    Window { source: WindowError },
+    #[snafu(context(false), display("IO error: {}", source))]
+    Io { source: std::io::Error },
+    #[snafu(context(false), display("Image error: {}", source))]
+    Image { source: image::ImageError },
+    #[snafu(context(false), display("JSON error: {}", source))]
+    Json { source: serde_json::Error },
+    #[snafu(display("Invalid texture atlas: {}", message))]
+    InvalidAtlas { message: String },
+    #[snafu(display("Invalid bitmap font: {}", message))]
+    InvalidFont { message: String },
+    #[snafu(display("Texture atlas region not found: {}", name))]
+    RegionNotFound { name: String },
+    #[snafu(display("Invalid argument: {}", message))]
+    InvalidArgument { message: String },
+    #[snafu(display("Framebuffer is incomplete (status {:#x})", status))]
+    IncompleteFramebuffer { status: u32 },
+    #[snafu(display("Unsupported: {}", message))]
+    Unsupported { message: String },
+}
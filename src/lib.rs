@@ -1,5 +1,9 @@
+pub mod error;
+pub mod math;
+pub mod prelude;
 pub mod rendering;
 pub mod utils;
 
+pub use error::*;
 pub use rendering::*;
 pub use utils::*;
@@ -0,0 +1,12 @@
+//! The nalgebra types used by the public API, so downstream crates don't need to depend
+//! on the same nalgebra version.
+
+pub use nalgebra;
+
+pub type Vec2 = nalgebra::Vector2<f32>;
+pub type Vec3 = nalgebra::Vector3<f32>;
+pub type Vec4 = nalgebra::Vector4<f32>;
+pub type Mat3 = nalgebra::Matrix3<f32>;
+pub type Mat4 = nalgebra::Matrix4<f32>;
+pub type Point2 = nalgebra::Point2<f32>;
+pub type Point3 = nalgebra::Point3<f32>;
@@ -0,0 +1,21 @@
+//! The commonly used types and traits, `use babo::prelude::*;` to import them all.
+
+pub use nalgebra::{Matrix4, Vector2, Vector3, Vector4};
+
+pub use crate::{
+    error::Error,
+    math::{Mat4, Vec2, Vec3, Vec4},
+    rendering::{
+        Animation, BlendMode, Camera, Color, DepthFunc, DrawParams, Event, FullscreenMode,
+        GamepadAxis, GamepadButton, Input, Key, LineRendererTrait, MouseButton, Node, Rect,
+        Renderer, ShapeRendererTrait, SortMode, SpriteInstance, SpriteRendererTrait, TextureAtlas,
+        TextureOptions, TextureTrait, TileMap, TileMapRendererTrait, Transform2D, VSync,
+        WindowOptions, WindowTrait,
+    },
+};
+
+#[cfg(feature = "opengl")]
+pub use crate::rendering::{LineRenderer, ShapeRenderer, SpriteRenderer, Texture, TileMapRenderer};
+
+#[cfg(feature = "sdl2")]
+pub use crate::rendering::Window;
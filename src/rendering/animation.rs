@@ -0,0 +1,168 @@
+use crate::rendering::Rect;
+
+/// A sequence of spritesheet frames, each shown for its own duration.
+///
+/// Feed `current_frame` into `SpriteRendererTrait::draw_region` or `DrawParams::source`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Animation {
+    frames: Vec<Rect>,
+    durations: Vec<f32>,
+    looping: bool,
+    current: usize,
+    elapsed: f32,
+    finished: bool,
+}
+
+impl Animation {
+    /// Create an animation where every frame is shown for `frame_duration` seconds.
+    pub fn new(frames: Vec<Rect>, frame_duration: f32, looping: bool) -> Self {
+        let frames = frames
+            .into_iter()
+            .map(|frame| (frame, frame_duration))
+            .collect();
+
+        Self::with_durations(frames, looping)
+    }
+
+    /// Create an animation from frames paired with how many seconds they are shown for.
+    pub fn with_durations(frames: Vec<(Rect, f32)>, looping: bool) -> Self {
+        let (frames, durations) = frames.into_iter().unzip();
+
+        Self {
+            frames,
+            durations,
+            looping,
+            current: 0,
+            elapsed: 0.0,
+            finished: false,
+        }
+    }
+
+    /// Advance the animation by `dt` seconds, skipping frames if `dt` is longer than a frame.
+    pub fn update(&mut self, dt: f32) {
+        if self.finished || self.frames.is_empty() {
+            return;
+        }
+
+        self.elapsed += dt;
+
+        while self.elapsed >= self.durations[self.current] {
+            // Zero length frames would loop forever.
+            if self.durations[self.current] <= 0.0 && self.current + 1 == self.frames.len() {
+                break;
+            }
+
+            self.elapsed -= self.durations[self.current];
+
+            if self.current + 1 < self.frames.len() {
+                self.current += 1;
+            } else if self.looping {
+                self.current = 0;
+            } else {
+                // Hold the last frame.
+                self.elapsed = 0.0;
+                self.finished = true;
+                break;
+            }
+        }
+    }
+
+    /// The region of the spritesheet to draw, an empty animation has an empty frame.
+    pub fn current_frame(&self) -> Rect {
+        self.frames.get(self.current).copied().unwrap_or_default()
+    }
+
+    pub fn current_index(&self) -> usize {
+        self.current
+    }
+
+    pub fn frames(&self) -> &[Rect] {
+        &self.frames
+    }
+
+    pub fn looping(&self) -> bool {
+        self.looping
+    }
+
+    pub fn set_looping(&mut self, looping: bool) {
+        self.looping = looping;
+    }
+
+    /// Whether a non-looping animation has reached the end of its last frame.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    /// Start playing again from the first frame.
+    pub fn reset(&mut self) {
+        self.current = 0;
+        self.elapsed = 0.0;
+        self.finished = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frames(count: usize) -> Vec<Rect> {
+        (0..count)
+            .map(|index| Rect::new(index as f32 * 16.0, 0.0, 16.0, 16.0))
+            .collect()
+    }
+
+    #[test]
+    fn update_advances_and_skips_frames() {
+        let mut animation = Animation::new(frames(4), 0.25, false);
+
+        animation.update(0.125);
+        assert_eq!(animation.current_index(), 0);
+        animation.update(0.125);
+        assert_eq!(animation.current_index(), 1);
+
+        // A long frame skips ahead, keeping the leftover time.
+        animation.update(0.625);
+        assert_eq!(animation.current_index(), 3);
+        assert_eq!(animation.current_frame(), frames(4)[3]);
+        animation.update(0.125);
+        assert!(animation.is_finished());
+    }
+
+    #[test]
+    fn looping_wraps_to_the_first_frame() {
+        let mut animation =
+            Animation::with_durations(frames(2).into_iter().zip([0.25, 0.5]).collect(), true);
+
+        animation.update(0.75);
+        assert_eq!(animation.current_index(), 0);
+        animation.update(0.5);
+        assert_eq!(animation.current_index(), 1);
+        assert!(!animation.is_finished());
+    }
+
+    #[test]
+    fn finished_animations_hold_the_last_frame_until_reset() {
+        let mut animation = Animation::new(frames(2), 0.25, false);
+
+        animation.update(10.0);
+        assert!(animation.is_finished());
+        assert_eq!(animation.current_index(), 1);
+
+        animation.update(0.25);
+        assert_eq!(animation.current_index(), 1);
+
+        animation.reset();
+        assert!(!animation.is_finished());
+        assert_eq!(animation.current_index(), 0);
+        animation.update(0.25);
+        assert_eq!(animation.current_index(), 1);
+    }
+
+    #[test]
+    fn empty_animations_have_an_empty_frame() {
+        let mut animation = Animation::new(Vec::new(), 0.25, true);
+        animation.update(1.0);
+
+        assert_eq!(animation.current_frame(), Rect::default());
+    }
+}
@@ -0,0 +1,506 @@
+use std::{collections::HashMap, path::Path};
+
+use nalgebra::{Matrix4, Vector2, Vector3};
+
+use crate::{
+    error::Error,
+    rendering::{Color, DrawParams, Rect, SpriteRendererTrait, TextureTrait},
+};
+
+/// A character in a bitmap font page.
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Glyph {
+    /// The region of the page in pixels.
+    region: Rect,
+    /// The offset from the pen position to the top-left of the glyph.
+    offset: Vector2<f32>,
+    /// How far to move the pen after the glyph.
+    advance: f32,
+    page: usize,
+}
+
+/// The placement of a single glyph, in pixels.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct GlyphMetrics {
+    /// The offset from the pen position, on the top of the line, to the top-left of the glyph.
+    pub bearing: Vector2<f32>,
+    /// How far the pen moves after the glyph, not including kerning.
+    pub advance: f32,
+    /// The size of the glyph image, zero for whitespace.
+    pub size: Vector2<f32>,
+}
+
+/// The distance between tab stops.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum TabWidth {
+    Pixels(f32),
+    /// A multiple of the advance of the space character.
+    Spaces(f32),
+}
+
+impl Default for TabWidth {
+    fn default() -> Self {
+        TabWidth::Spaces(4.0)
+    }
+}
+
+/// A font rendered ahead of time into texture pages, loaded from an AngelCode BMFont
+/// text descriptor (`.fnt`).
+///
+/// Text is drawn with a sprite renderer, one sprite per glyph, with `\n` starting a new line
+/// and `\t` moving to the next tab stop.
+pub struct BitmapFont<T> {
+    pages: Vec<T>,
+    glyphs: HashMap<char, Glyph>,
+    kerning: HashMap<(char, char), f32>,
+    line_height: f32,
+    base: f32,
+    tab_width: TabWidth,
+}
+
+impl<T> BitmapFont<T> {
+    /// Create a font from the text of a `.fnt` descriptor and its pages, ordered by page id.
+    pub fn from_fnt(pages: Vec<T>, descriptor: &str) -> Result<Self, Error> {
+        let mut font = Self {
+            pages,
+            glyphs: HashMap::new(),
+            kerning: HashMap::new(),
+            line_height: 0.0,
+            base: 0.0,
+            tab_width: TabWidth::default(),
+        };
+
+        for (tag, attributes) in descriptor.lines().filter_map(parse_line) {
+            let field = |key: &str| {
+                attributes
+                    .get(key)
+                    .and_then(|value| value.parse::<f32>().ok())
+                    .ok_or_else(|| Error::InvalidFont {
+                        message: format!("\"{}\" is missing a valid \"{}\"", tag, key),
+                    })
+            };
+            let character = |key: &str| {
+                field(key).and_then(|id| {
+                    char::from_u32(id as u32).ok_or_else(|| Error::InvalidFont {
+                        message: format!("\"{}\" has an invalid character {}", tag, id),
+                    })
+                })
+            };
+
+            match tag {
+                "common" => {
+                    font.line_height = field("lineHeight")?;
+                    font.base = field("base")?;
+                }
+                "char" => {
+                    // Single page fonts can leave the page out.
+                    let page = field("page").unwrap_or(0.0) as usize;
+                    if page >= font.pages.len() {
+                        return Err(Error::InvalidFont {
+                            message: format!("page {} is out of range", page),
+                        });
+                    }
+
+                    let glyph = Glyph {
+                        region: Rect::new(
+                            field("x")?,
+                            field("y")?,
+                            field("width")?,
+                            field("height")?,
+                        ),
+                        offset: Vector2::new(field("xoffset")?, field("yoffset")?),
+                        advance: field("xadvance")?,
+                        page,
+                    };
+                    font.glyphs.insert(character("id")?, glyph);
+                }
+                "kerning" => {
+                    font.kerning.insert(
+                        (character("first")?, character("second")?),
+                        field("amount")?,
+                    );
+                }
+                _ => {}
+            }
+        }
+
+        Ok(font)
+    }
+
+    /// The distance between the tops of two lines, in pixels.
+    pub fn line_height(&self) -> f32 {
+        self.line_height
+    }
+
+    /// The distance from the top of a line to the baseline, in pixels.
+    pub fn base(&self) -> f32 {
+        self.base
+    }
+
+    /// Set the distance between the tab stops `\t` moves to, measured from the start of
+    /// the line.
+    pub fn set_tab_width(&mut self, tab_width: TabWidth) {
+        self.tab_width = tab_width;
+    }
+
+    pub fn tab_width(&self) -> TabWidth {
+        self.tab_width
+    }
+
+    /// The tab width in pixels.
+    fn tab_pixels(&self) -> f32 {
+        match self.tab_width {
+            TabWidth::Pixels(pixels) => pixels,
+            TabWidth::Spaces(spaces) => {
+                spaces * self.glyphs.get(&' ').map_or(0.0, |space| space.advance)
+            }
+        }
+    }
+
+    pub fn pages(&self) -> &[T] {
+        &self.pages
+    }
+
+    pub fn has_glyph(&self, character: char) -> bool {
+        self.glyphs.contains_key(&character)
+    }
+
+    /// The metrics of a character, `None` if the font doesn't have it.
+    pub fn glyph_metrics(&self, character: char) -> Option<GlyphMetrics> {
+        self.glyphs.get(&character).map(|glyph| GlyphMetrics {
+            bearing: glyph.offset,
+            advance: glyph.advance,
+            size: Vector2::new(glyph.region.width, glyph.region.height),
+        })
+    }
+
+    /// The adjustment to the advance between two characters, 0 for most pairs.
+    pub fn kerning(&self, first: char, second: char) -> f32 {
+        self.kerning.get(&(first, second)).copied().unwrap_or(0.0)
+    }
+
+    /// Lay out `text` with its top-left at `position`, calling `glyph` with the page, the
+    /// source region and the position of each visible glyph. Returns the size of the text and
+    /// the final pen position, relative to `position`.
+    ///
+    /// Characters missing from the font fall back to `?`, or are skipped without it.
+    fn layout(
+        &self,
+        text: &str,
+        position: Vector2<f32>,
+        mut glyph: impl FnMut(usize, Rect, Vector2<f32>),
+    ) -> (Vector2<f32>, Vector2<f32>) {
+        let mut pen = position;
+        let mut width: f32 = 0.0;
+        let mut previous = None;
+        let tab = self.tab_pixels();
+
+        for character in text.chars() {
+            if character == '\n' {
+                pen = Vector2::new(position.x, pen.y + self.line_height);
+                previous = None;
+                continue;
+            }
+
+            // Fonts without a space can't measure tabs in spaces, they're skipped.
+            if character == '\t' {
+                if tab > 0.0 {
+                    let column = ((pen.x - position.x) / tab).floor() + 1.0;
+                    pen.x = position.x + column * tab;
+                    width = width.max(pen.x - position.x);
+                }
+                previous = None;
+                continue;
+            }
+
+            let Some(current) = self
+                .glyphs
+                .get(&character)
+                .or_else(|| self.glyphs.get(&'?'))
+            else {
+                continue;
+            };
+
+            if let Some(previous) = previous {
+                pen.x += self.kerning(previous, character);
+            }
+
+            if current.region.width > 0.0 && current.region.height > 0.0 {
+                glyph(current.page, current.region, pen + current.offset);
+            }
+
+            pen.x += current.advance;
+            width = width.max(pen.x - position.x);
+            previous = Some(character);
+        }
+
+        let pen = pen - position;
+        (Vector2::new(width, pen.y + self.line_height), pen)
+    }
+
+    /// The size of the area covered by `text`, the width of the widest line by the height of
+    /// all of the lines.
+    pub fn measure(&self, text: &str) -> Vector2<f32> {
+        self.layout(text, Vector2::zeros(), |_, _, _| {}).0
+    }
+
+    /// The position of a text cursor after the first `index` characters of `text`, relative
+    /// to the top-left of the text and on the top of its line.
+    pub fn caret_position(&self, text: &str, index: usize) -> Vector2<f32> {
+        let end = text
+            .char_indices()
+            .nth(index)
+            .map_or(text.len(), |(end, _)| end);
+
+        self.layout(&text[..end], Vector2::zeros(), |_, _, _| {}).1
+    }
+
+    /// Draw `text` with its top-left at `position`, multiplying the glyphs by `color`.
+    pub fn draw<R>(
+        &self,
+        renderer: &R,
+        projection: &Matrix4<f32>,
+        view: &Matrix4<f32>,
+        text: &str,
+        position: Vector2<f32>,
+        color: impl Into<Color>,
+    ) -> Result<(), Error>
+    where
+        R: SpriteRendererTrait<Texture = T>,
+    {
+        let params = DrawParams::new().origin(Vector2::zeros()).color(color);
+        let mut result = Ok(());
+
+        self.layout(text, position, |page, region, position| {
+            if result.is_err() {
+                return;
+            }
+
+            result = renderer.draw_params(
+                &self.pages[page],
+                projection,
+                view,
+                &params
+                    .position(Vector3::new(position.x, position.y, 0.0))
+                    .size(Vector2::new(region.width, region.height))
+                    .source(region),
+            );
+        });
+
+        result
+    }
+}
+
+impl<T: TextureTrait> BitmapFont<T> {
+    /// Load a `.fnt` descriptor and its pages, page files are relative to the descriptor.
+    pub fn from_file(path: &str) -> Result<Self, Error> {
+        let descriptor = std::fs::read_to_string(path)?;
+        let directory = Path::new(path).parent().unwrap_or_else(|| Path::new(""));
+
+        let mut files = descriptor
+            .lines()
+            .filter_map(parse_line)
+            .filter(|(tag, _)| *tag == "page")
+            .map(|(_, attributes)| {
+                let id = attributes.get("id").and_then(|id| id.parse::<usize>().ok());
+                match (id, attributes.get("file")) {
+                    (Some(id), Some(file)) => Ok((id, directory.join(file))),
+                    _ => Err(Error::InvalidFont {
+                        message: "\"page\" is missing a valid \"id\" or \"file\"".to_string(),
+                    }),
+                }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        files.sort_by_key(|(id, _)| *id);
+
+        let pages = files
+            .iter()
+            .map(|(_, file)| T::from_file(&file.to_string_lossy()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Self::from_fnt(pages, &descriptor)
+    }
+}
+
+/// Split a descriptor line into its tag and `key=value` attributes, values can be quoted.
+fn parse_line(line: &str) -> Option<(&str, HashMap<&str, &str>)> {
+    let line = line.trim();
+    let (tag, mut rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+    if tag.is_empty() {
+        return None;
+    }
+
+    let mut attributes = HashMap::new();
+    loop {
+        rest = rest.trim_start();
+        let Some((key, value)) = rest.split_once('=') else {
+            break;
+        };
+
+        let (value, remaining) = match value.strip_prefix('"') {
+            Some(quoted) => quoted.split_once('"').unwrap_or((quoted, "")),
+            None => value.split_once(char::is_whitespace).unwrap_or((value, "")),
+        };
+
+        attributes.insert(key.trim(), value);
+        rest = remaining;
+    }
+
+    Some((tag, attributes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DESCRIPTOR: &str = r#"info face="Test Font" size=16
+common lineHeight=20 base=16 scaleW=64 scaleH=64 pages=1
+page id=0 file="test font.png"
+chars count=5
+char id=32 x=0 y=0 width=0 height=0 xoffset=0 yoffset=0 xadvance=4 page=0
+char id=97 x=0 y=0 width=8 height=10 xoffset=1 yoffset=6 xadvance=9 page=0
+char id=98 x=8 y=0 width=8 height=14 xoffset=1 yoffset=2 xadvance=9 page=0
+char id=65 x=16 y=0 width=10 height=14 xoffset=0 yoffset=2 xadvance=10 page=0
+char id=86 x=26 y=0 width=10 height=14 xoffset=0 yoffset=2 xadvance=10 page=0
+kernings count=1
+kerning first=65 second=86 amount=-2
+"#;
+
+    fn font() -> BitmapFont<()> {
+        BitmapFont::from_fnt(vec![()], DESCRIPTOR).unwrap()
+    }
+
+    /// The position of every visible glyph when laying out `text` at `position`.
+    fn glyph_positions(
+        font: &BitmapFont<()>,
+        text: &str,
+        position: Vector2<f32>,
+    ) -> Vec<Vector2<f32>> {
+        let mut positions = Vec::new();
+        font.layout(text, position, |_, _, position| positions.push(position));
+        positions
+    }
+
+    #[test]
+    fn newline_returns_to_the_start_of_the_line() {
+        let positions = glyph_positions(&font(), "a\nb", Vector2::new(5.0, 5.0));
+
+        // Each glyph sits at the pen plus its offset, the second line one line height down.
+        assert_eq!(
+            positions,
+            vec![Vector2::new(6.0, 11.0), Vector2::new(6.0, 27.0)]
+        );
+    }
+
+    #[test]
+    fn measure_covers_the_widest_line_and_every_line() {
+        let font = font();
+
+        assert_eq!(font.measure(""), Vector2::new(0.0, 20.0));
+        assert_eq!(font.measure("ab"), Vector2::new(18.0, 20.0));
+        assert_eq!(font.measure("ab\na"), Vector2::new(18.0, 40.0));
+    }
+
+    #[test]
+    fn caret_position_follows_the_pen() {
+        let font = font();
+
+        assert_eq!(font.caret_position("ab\na", 0), Vector2::new(0.0, 0.0));
+        assert_eq!(font.caret_position("ab\na", 2), Vector2::new(18.0, 0.0));
+        assert_eq!(font.caret_position("ab\na", 3), Vector2::new(0.0, 20.0));
+        // Past the end is the end of the text.
+        assert_eq!(font.caret_position("ab\na", 10), Vector2::new(9.0, 20.0));
+    }
+
+    #[test]
+    fn tabs_move_to_the_next_tab_stop() {
+        let mut font = font();
+
+        // Four spaces of 4 pixels by default.
+        assert_eq!(font.caret_position("a\tb", 2), Vector2::new(16.0, 0.0));
+        assert_eq!(font.caret_position("\t", 1), Vector2::new(16.0, 0.0));
+        assert_eq!(font.measure("a\tb"), Vector2::new(25.0, 20.0));
+
+        font.set_tab_width(TabWidth::Pixels(10.0));
+        assert_eq!(font.caret_position("a\tb", 2), Vector2::new(10.0, 0.0));
+        assert_eq!(font.caret_position("ab\t", 3), Vector2::new(20.0, 0.0));
+    }
+
+    #[test]
+    fn kerning_pulls_pairs_together() {
+        let font = font();
+
+        assert_eq!(font.kerning('A', 'V'), -2.0);
+        assert_eq!(font.kerning('V', 'A'), 0.0);
+
+        let unkerned = font.measure("A").x + font.measure("V").x;
+        assert_eq!(font.measure("AV").x, unkerned - 2.0);
+        assert_eq!(font.measure("VA").x, unkerned);
+
+        // Pairs don't kern across line breaks.
+        let positions = glyph_positions(&font, "A\nV", Vector2::zeros());
+        assert_eq!(positions[1], Vector2::new(0.0, 22.0));
+    }
+
+    #[test]
+    fn parse_line_reads_quoted_values() {
+        let (tag, attributes) = parse_line(r#"page id=0 file="test font.png""#).unwrap();
+
+        assert_eq!(tag, "page");
+        assert_eq!(attributes["id"], "0");
+        assert_eq!(attributes["file"], "test font.png");
+        assert!(parse_line("   ").is_none());
+    }
+
+    #[test]
+    fn from_fnt_reads_common_and_glyphs() {
+        let font = font();
+
+        assert_eq!(font.line_height(), 20.0);
+        assert_eq!(font.base(), 16.0);
+        assert_eq!(
+            font.glyph_metrics('b'),
+            Some(GlyphMetrics {
+                bearing: Vector2::new(1.0, 2.0),
+                advance: 9.0,
+                size: Vector2::new(8.0, 14.0),
+            })
+        );
+        assert_eq!(font.glyph_metrics('z'), None);
+    }
+
+    #[test]
+    fn from_fnt_defaults_to_the_first_page() {
+        let font = BitmapFont::from_fnt(
+            vec![()],
+            "char id=97 x=0 y=0 width=8 height=10 xoffset=0 yoffset=0 xadvance=9",
+        )
+        .unwrap();
+
+        assert!(font.has_glyph('a'));
+    }
+
+    #[test]
+    fn from_fnt_rejects_invalid_glyphs() {
+        let invalid = [
+            // Out of range page.
+            "char id=97 x=0 y=0 width=8 height=10 xoffset=0 yoffset=0 xadvance=9 page=1",
+            // A surrogate isn't a character.
+            "char id=55296 x=0 y=0 width=8 height=10 xoffset=0 yoffset=0 xadvance=9",
+            "char id=abc x=0 y=0 width=8 height=10 xoffset=0 yoffset=0 xadvance=9",
+            // Missing the advance.
+            "char id=97 x=0 y=0 width=8 height=10 xoffset=0 yoffset=0",
+            "kerning first=65 second=86",
+        ];
+
+        for descriptor in invalid {
+            assert!(
+                matches!(
+                    BitmapFont::from_fnt(vec![()], descriptor),
+                    Err(Error::InvalidFont { .. })
+                ),
+                "accepted {descriptor:?}"
+            );
+        }
+    }
+}
@@ -0,0 +1,13 @@
+/// How drawn colors are combined with what is already in the framebuffer.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum BlendMode {
+    /// Regular transparency using the source alpha.
+    #[default]
+    Alpha,
+    /// Adds the source color on top, useful for glows and lights.
+    Additive,
+    /// Multiplies with the destination color, useful for shadows and tinting.
+    Multiply,
+    /// Transparency for textures with their color already multiplied by alpha.
+    Premultiplied,
+}
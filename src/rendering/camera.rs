@@ -1,75 +1,401 @@
 #![allow(unused)]
 
-use nalgebra::{Matrix4, Orthographic3, Vector2, Vector3};
+use std::cell::OnceCell;
+
+use nalgebra::{Matrix4, Orthographic3, Perspective3, Point3, Vector2, Vector3};
 
 use crate::WindowTrait;
 
 pub struct Camera {
-    projection: Orthographic3<f32>,
+    projection: Matrix4<f32>,
+    perspective: Option<Perspective>,
+    y_down: bool,
     screen: Vector2<f32>,
+    /// The view matrix, cleared whenever anything it's built from changes.
+    view: OnceCell<Matrix4<f32>>,
+    view_projection: OnceCell<Matrix4<f32>>,
     position: Vector2<f32>,
     zoom: Vector2<f32>,
     rotation: f32,
+    bounds: Option<(Vector2<f32>, Vector2<f32>)>,
+    shake: Shake,
+    viewport: Option<(i32, i32, i32, i32)>,
+}
+
+/// The settings of a perspective projection, the aspect ratio follows the screen.
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Perspective {
+    fov: f32,
+    near: f32,
+    far: f32,
+}
+
+/// Decaying random offset applied on top of the camera position.
+struct Shake {
+    intensity: f32,
+    duration: f32,
+    elapsed: f32,
+    offset: Vector2<f32>,
+    rng: u64,
+}
+
+impl Shake {
+    const DEFAULT_SEED: u64 = 0x9E37_79B9_7F4A_7C15;
+
+    fn new(seed: u64) -> Self {
+        Self {
+            intensity: 0.0,
+            duration: 0.0,
+            elapsed: 0.0,
+            offset: Vector2::zeros(),
+            // Xorshift gets stuck on a zero state.
+            rng: if seed == 0 { Self::DEFAULT_SEED } else { seed },
+        }
+    }
+
+    /// Get the next random value in the range [-1, 1] using xorshift64.
+    fn next_random(&mut self) -> f32 {
+        self.rng ^= self.rng << 13;
+        self.rng ^= self.rng >> 7;
+        self.rng ^= self.rng << 17;
+        (self.rng >> 40) as f32 / (1u64 << 23) as f32 - 1.0
+    }
+
+    fn update(&mut self, dt: f32) {
+        self.elapsed += dt;
+
+        if self.elapsed >= self.duration {
+            self.intensity = 0.0;
+            self.offset = Vector2::zeros();
+            return;
+        }
+
+        let strength = self.intensity * (1.0 - self.elapsed / self.duration);
+        self.offset = Vector2::new(self.next_random(), self.next_random()) * strength;
+    }
+}
+
+/// Unproject a point in normalized device coordinates onto the `z = 0` world plane, given the
+/// inverse of a view projection matrix.
+///
+/// Casts a ray from the near to the far plane, so it works for perspective projections too.
+pub(crate) fn unproject_to_plane(inverse: &Matrix4<f32>, ndc: Vector2<f32>) -> Vector2<f32> {
+    let near = inverse.transform_point(&Point3::new(ndc.x, ndc.y, -1.0));
+    let far = inverse.transform_point(&Point3::new(ndc.x, ndc.y, 1.0));
+    let t = match far.z - near.z {
+        depth if depth.abs() > f32::EPSILON => -near.z / depth,
+        _ => 0.0,
+    };
+    let world = near + (far - near) * t;
+
+    Vector2::new(world.x, world.y)
 }
 
 impl Camera {
     pub fn new(width: f32, height: f32) -> Self {
         Self {
             screen: Vector2::new(width, height),
-            projection: Orthographic3::new(0.0, width, height, 0.0, -1.0, 1.0),
+            projection: Orthographic3::new(0.0, width, height, 0.0, -1.0, 1.0).to_homogeneous(),
+            perspective: None,
+            y_down: true,
+            view: OnceCell::new(),
+            view_projection: OnceCell::new(),
             position: Vector2::new(0.0, 0.0),
             zoom: Vector2::new(1.0, 1.0),
             rotation: 0.0,
+            bounds: None,
+            shake: Shake::new(Shake::DEFAULT_SEED),
+            viewport: None,
         }
     }
 
-    pub fn view(&self) -> Matrix4<f32> {
-        let mut view = Matrix4::identity();
+    /// Create a camera with a perspective projection for a screen of `width` by `height`,
+    /// `fov` is the vertical field of view in radians.
+    ///
+    /// The camera looks at the `z = 0` plane from far enough away that sprites on it keep
+    /// their size in pixels, so 2D scenes look the same as with an orthographic camera.
+    /// Sprites with a higher `z` are closer to the camera and appear larger and move faster,
+    /// which is what gives parallax. `near` and `far` are distances from the camera, which
+    /// sits `height / 2 / tan(fov / 2)` units in front of the plane.
+    pub fn perspective(width: f32, height: f32, fov: f32, near: f32, far: f32) -> Self {
+        let mut camera = Self::new(width, height);
+        camera.set_perspective(fov, near, far);
+        camera
+    }
+
+    /// Switch to a perspective projection, see `perspective`.
+    pub fn set_perspective(&mut self, fov: f32, near: f32, far: f32) {
+        self.perspective = Some(Perspective { fov, near, far });
+        self.update_projection();
+    }
+
+    /// Switch back to the default orthographic projection.
+    pub fn set_orthographic(&mut self) {
+        self.perspective = None;
+        self.update_projection();
+    }
+
+    pub fn is_perspective(&self) -> bool {
+        self.perspective.is_some()
+    }
+
+    /// Choose whether world Y points down with a top-left origin (the default), or up with a
+    /// bottom-left origin like most physics libraries.
+    ///
+    /// Screen coordinates in `world_to_screen` and `screen_to_world` keep a top-left origin
+    /// either way. With Y up textures are drawn upside down, flip them with `DrawParams::flip`.
+    pub fn set_y_down(&mut self, y_down: bool) {
+        self.y_down = y_down;
+        self.update_projection();
+    }
+
+    pub fn y_down(&self) -> bool {
+        self.y_down
+    }
+
+    /// The view matrix, only rebuilt after the camera has changed.
+    pub fn view(&self) -> &Matrix4<f32> {
+        self.view.get_or_init(|| self.build_view())
+    }
 
-        // Center the camera.
-        view *=
-            Matrix4::new_translation(&Vector3::new(self.screen.x / 2.0, self.screen.y / 2.0, 0.0));
+    /// The projection and view matrices multiplied together, only rebuilt after the camera
+    /// has changed.
+    pub fn view_projection(&self) -> &Matrix4<f32> {
+        self.view_projection
+            .get_or_init(|| self.projection() * self.view())
+    }
 
-        // Set the position.
-        view *= Matrix4::new_translation(&Vector3::new(-self.position.x, -self.position.y, 0.0));
+    /// Clear the cached matrices, so they're rebuilt on the next use.
+    fn invalidate_view(&mut self) {
+        self.view.take();
+        self.view_projection.take();
+    }
 
-        // Rotate around the origin.
-        view *= Matrix4::new_translation(&Vector3::new(-self.position.x, -self.position.y, 0.0));
-        view *= Matrix4::new_rotation(&Vector3::z() * self.rotation);
-        view *= Matrix4::new_translation(&Vector3::new(self.position.x, self.position.y, 0.0));
+    fn build_view(&self) -> Matrix4<f32> {
+        let extent = self.extent();
+        let center = Vector3::new(extent.x / 2.0, extent.y / 2.0, 0.0);
+        let focus = self.position + self.shake.offset;
 
         // Zoom around the center of the screen.
-        view *=
-            Matrix4::new_translation(&Vector3::new(self.screen.x / 2.0, self.screen.y / 2.0, 0.0));
-        view *= Matrix4::new_nonuniform_scaling(&Vector3::new(self.zoom.x, self.zoom.y, 1.0));
-        view *= Matrix4::new_translation(&Vector3::new(
-            -self.screen.x / 2.0,
-            -self.screen.y / 2.0,
-            0.0,
-        ));
+        let mut view = Matrix4::new_translation(&center)
+            * Matrix4::new_nonuniform_scaling(&Vector3::new(self.zoom.x, self.zoom.y, 1.0));
+
+        // Rotate around the camera position.
+        view *= Matrix4::new_rotation(Vector3::z() * self.rotation);
+
+        // Move the camera position to the origin, applying any shake without moving the
+        // logical position.
+        view *= Matrix4::new_translation(&Vector3::new(-focus.x, -focus.y, 0.0));
 
         view
     }
 
     pub fn projection(&self) -> &Matrix4<f32> {
-        &self.projection.as_matrix()
+        &self.projection
+    }
+
+    /// Project a world space point to screen space pixel coordinates (top-left origin).
+    ///
+    /// When a viewport is set the coordinates are relative to the viewport.
+    pub fn world_to_screen(&self, world: Vector2<f32>) -> Vector2<f32> {
+        let extent = self.extent();
+        let ndc = self
+            .view_projection()
+            .transform_point(&Point3::new(world.x, world.y, 0.0));
+
+        // Map from NDC back to pixels, flipping Y for the top-left origin.
+        Vector2::new(
+            (ndc.x + 1.0) / 2.0 * extent.x,
+            (1.0 - ndc.y) / 2.0 * extent.y,
+        )
+    }
+
+    /// Unproject a screen space pixel coordinate (top-left origin) into world space, on the
+    /// `z = 0` plane.
+    ///
+    /// When a viewport is set the coordinates are relative to the viewport.
+    pub fn screen_to_world(&self, screen: Vector2<f32>) -> Vector2<f32> {
+        let extent = self.extent();
+
+        // Map from pixels to NDC, flipping Y for the top-left origin.
+        let ndc_x = screen.x / extent.x * 2.0 - 1.0;
+        let ndc_y = 1.0 - screen.y / extent.y * 2.0;
+
+        let inverse = self
+            .view_projection()
+            .try_inverse()
+            .unwrap_or_else(Matrix4::identity);
+
+        unproject_to_plane(&inverse, Vector2::new(ndc_x, ndc_y))
+    }
+
+    pub fn position(&self) -> Vector2<f32> {
+        self.position
+    }
+
+    pub fn zoom(&self) -> Vector2<f32> {
+        self.zoom
+    }
+
+    pub fn rotation(&self) -> f32 {
+        self.rotation
     }
 
     pub fn set_position(&mut self, position: Vector2<f32>) {
-        self.position = position;
+        self.position = self.clamp_to_bounds(position);
+        self.invalidate_view();
+    }
+
+    /// Smoothly move the camera towards a target, independent of the framerate.
+    ///
+    /// `smoothing` is the fraction of the remaining distance kept per 60hz frame,
+    /// so `0.0` snaps to the target immediately and values closer to `1.0` trail further behind.
+    pub fn follow(&mut self, target: Vector2<f32>, smoothing: f32, dt: f32) {
+        if smoothing <= 0.0 {
+            self.set_position(target);
+            return;
+        }
+
+        let t = 1.0 - smoothing.min(1.0).powf(dt * 60.0);
+        self.set_position(self.position.lerp(&target, t));
+    }
+
+    /// Shake the camera with an offset of up to `intensity` units, decaying over `duration` seconds.
+    pub fn shake(&mut self, intensity: f32, duration: f32) {
+        self.shake.intensity = intensity;
+        self.shake.duration = duration;
+        self.shake.elapsed = 0.0;
+    }
+
+    /// Seed the random number generator used for shaking, making the offsets deterministic.
+    pub fn set_shake_seed(&mut self, seed: Option<u64>) {
+        self.shake.rng = Shake::new(seed.unwrap_or(Shake::DEFAULT_SEED)).rng;
+    }
+
+    /// Advance time based effects on the camera, should be called once per frame.
+    pub fn update(&mut self, dt: f32) {
+        let offset = self.shake.offset;
+        self.shake.update(dt);
+
+        if self.shake.offset != offset {
+            self.invalidate_view();
+        }
     }
 
     pub fn set_zoom(&mut self, zoom: Vector2<f32>) {
         self.zoom = zoom;
+        self.set_position(self.position);
+    }
+
+    /// Multiply the zoom by `factor` while keeping `focus_world` under the same screen pixel.
+    pub fn zoom_to(&mut self, factor: Vector2<f32>, focus_world: Vector2<f32>) {
+        let focus_screen = self.world_to_screen(focus_world);
+
+        self.zoom = self.zoom.component_mul(&factor);
+        self.invalidate_view();
+
+        // Move the camera by however far the focus drifted away from its pixel.
+        let drifted = self.screen_to_world(focus_screen);
+        self.set_position(self.position + focus_world - drifted);
     }
 
     pub fn set_rotation(&mut self, rotation: f32) {
         self.rotation = rotation;
+        self.invalidate_view();
     }
 
     pub fn set_screen(&mut self, width: f32, height: f32) {
         self.screen = Vector2::new(width, height);
-        self.projection = Orthographic3::new(0.0, width, height, 0.0, -1.0, 1.0);
+        self.update_projection();
+    }
+
+    /// Render into a sub-rectangle of the window, in drawable pixels with a bottom-left origin.
+    ///
+    /// The projection is computed from the viewport size instead of the screen size, the
+    /// window still has to be told to apply it with `WindowTrait::set_viewport`.
+    /// An unset (`None`) viewport covers the full window.
+    pub fn set_viewport(&mut self, x: i32, y: i32, width: i32, height: i32) {
+        self.viewport = Some((x, y, width, height));
+        self.update_projection();
+    }
+
+    pub fn clear_viewport(&mut self) {
+        self.viewport = None;
+        self.update_projection();
+    }
+
+    pub fn viewport(&self) -> Option<(i32, i32, i32, i32)> {
+        self.viewport
+    }
+
+    /// The size of the area being rendered into, either the viewport or the whole screen.
+    fn extent(&self) -> Vector2<f32> {
+        match self.viewport {
+            Some((_, _, width, height)) => Vector2::new(width as f32, height as f32),
+            None => self.screen,
+        }
+    }
+
+    fn update_projection(&mut self) {
+        let extent = self.extent();
+
+        let (bottom, top, flip_y) = match self.y_down {
+            true => (extent.y, 0.0, -1.0),
+            false => (0.0, extent.y, 1.0),
+        };
+
+        self.projection = match self.perspective {
+            None => Orthographic3::new(0.0, extent.x, bottom, top, -1.0, 1.0).to_homogeneous(),
+            Some(Perspective { fov, near, far }) => {
+                // Back away until the z = 0 plane fills the screen, then map the pixel
+                // coordinates to the centered, y-up camera space.
+                let distance = extent.y / 2.0 / (fov / 2.0).tan();
+                let to_camera = Matrix4::new_nonuniform_scaling(&Vector3::new(1.0, flip_y, 1.0))
+                    * Matrix4::new_translation(&Vector3::new(
+                        -extent.x / 2.0,
+                        -extent.y / 2.0,
+                        -distance,
+                    ));
+
+                Perspective3::new(extent.x / extent.y, fov, near, far).to_homogeneous() * to_camera
+            }
+        };
+
+        self.set_position(self.position);
+    }
+
+    /// Keep the visible area of the camera inside of the given world bounds.
+    pub fn set_bounds(&mut self, min: Vector2<f32>, max: Vector2<f32>) {
+        self.bounds = Some((min, max));
+        self.set_position(self.position);
+    }
+
+    pub fn clear_bounds(&mut self) {
+        self.bounds = None;
+    }
+
+    fn clamp_to_bounds(&self, position: Vector2<f32>) -> Vector2<f32> {
+        let (min, max) = match self.bounds {
+            Some(bounds) => bounds,
+            None => return position,
+        };
+
+        // The camera position is the center of the visible area.
+        let half_extents = self.extent().component_div(&self.zoom) / 2.0;
+
+        let clamp_axis = |position: f32, min: f32, max: f32, half_extent: f32| {
+            // Center on the axis if the world is smaller than the visible area.
+            if max - min <= half_extent * 2.0 {
+                (min + max) / 2.0
+            } else {
+                position.clamp(min + half_extent, max - half_extent)
+            }
+        };
+
+        Vector2::new(
+            clamp_axis(position.x, min.x, max.x, half_extents.x.abs()),
+            clamp_axis(position.y, min.y, max.y, half_extents.y.abs()),
+        )
     }
 }
 
@@ -78,6 +404,111 @@ where
     T: WindowTrait<'a>,
 {
     fn from(window: &T) -> Self {
-        Self::new(window.width() as f32, window.height() as f32)
+        let (width, height) = window.drawable_size();
+        Self::new(width as f32, height as f32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::FRAC_PI_2;
+
+    const EPSILON: f32 = 1e-4;
+
+    fn assert_close(actual: Vector2<f32>, expected: Vector2<f32>) {
+        assert!(
+            (actual - expected).abs().max() < EPSILON,
+            "expected {expected:?}, got {actual:?}"
+        );
+    }
+
+    #[test]
+    fn view_projection_rotates_and_zooms_around_the_position() {
+        let mut camera = Camera::new(800.0, 600.0);
+        camera.set_position(Vector2::new(100.0, 50.0));
+        camera.set_rotation(FRAC_PI_2);
+        camera.set_zoom(Vector2::new(2.0, 2.0));
+
+        // 10 units right of the camera, rotated a quarter turn to 10 units below it and
+        // zoomed to 20 pixels below the center of the screen, at (400, 320).
+        let ndc = camera
+            .view_projection()
+            .transform_point(&Point3::new(110.0, 50.0, 0.0));
+
+        assert_close(
+            Vector2::new(ndc.x, ndc.y),
+            Vector2::new(0.0, 1.0 - 320.0 / 300.0),
+        );
+    }
+
+    #[test]
+    fn screen_to_world_round_trips_through_world_to_screen() {
+        for y_down in [true, false] {
+            let mut camera = Camera::new(800.0, 600.0);
+            camera.set_y_down(y_down);
+            camera.set_viewport(100, 50, 400, 300);
+            camera.set_position(Vector2::new(-35.0, 120.0));
+            camera.set_rotation(0.7);
+            camera.set_zoom(Vector2::new(2.5, 1.5));
+
+            for screen in [
+                Vector2::new(0.0, 0.0),
+                Vector2::new(123.0, 45.0),
+                Vector2::new(400.0, 300.0),
+            ] {
+                let world = camera.screen_to_world(screen);
+                assert_close(camera.world_to_screen(world), screen);
+            }
+        }
+    }
+
+    #[test]
+    fn shake_is_deterministic_for_a_seed() {
+        let mut a = Camera::new(800.0, 600.0);
+        let mut b = Camera::new(800.0, 600.0);
+
+        for camera in [&mut a, &mut b] {
+            camera.set_shake_seed(Some(42));
+            camera.shake(8.0, 1.0);
+        }
+
+        for _ in 0..10 {
+            a.update(1.0 / 60.0);
+            b.update(1.0 / 60.0);
+            assert_eq!(a.view(), b.view());
+        }
+        assert_ne!(a.shake.offset, Vector2::zeros());
+    }
+
+    #[test]
+    fn shake_stops_after_the_duration() {
+        let mut camera = Camera::new(800.0, 600.0);
+        camera.shake(8.0, 0.5);
+
+        camera.update(0.25);
+        assert_ne!(camera.shake.offset, Vector2::zeros());
+
+        camera.update(0.25);
+        assert_eq!(camera.shake.offset, Vector2::zeros());
+        assert_eq!(camera.view(), Camera::new(800.0, 600.0).view());
+    }
+
+    #[test]
+    fn unproject_to_plane_matches_orthographic_under_perspective() {
+        let orthographic = Camera::new(800.0, 600.0);
+        let perspective = Camera::perspective(800.0, 600.0, FRAC_PI_2 / 2.0, 100.0, 1000.0);
+
+        for corner in [Vector2::new(-1.0, -1.0), Vector2::new(1.0, 1.0)] {
+            let [expected, actual] = [&orthographic, &perspective].map(|camera| {
+                let inverse = camera.view_projection().try_inverse().unwrap();
+                unproject_to_plane(&inverse, corner)
+            });
+            // Perspective loses a little precision in the depth, allow for it.
+            assert!(
+                (actual - expected).norm() < 1e-2,
+                "expected {expected:?}, got {actual:?}"
+            );
+        }
     }
 }
@@ -0,0 +1,91 @@
+use nalgebra::{Vector3, Vector4};
+
+/// A color with red, green, blue and alpha components in the range [0, 1].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Color {
+    pub r: f32,
+    pub g: f32,
+    pub b: f32,
+    pub a: f32,
+}
+
+impl Color {
+    pub const WHITE: Color = Color::rgb(1.0, 1.0, 1.0);
+    pub const BLACK: Color = Color::rgb(0.0, 0.0, 0.0);
+    pub const TRANSPARENT: Color = Color::rgba(0.0, 0.0, 0.0, 0.0);
+
+    pub const fn rgb(r: f32, g: f32, b: f32) -> Self {
+        Self { r, g, b, a: 1.0 }
+    }
+
+    pub const fn rgba(r: f32, g: f32, b: f32, a: f32) -> Self {
+        Self { r, g, b, a }
+    }
+
+    /// Create an opaque color from a hex value in the form `0xRRGGBB`.
+    pub fn hex(value: u32) -> Self {
+        let channel = |shift: u32| ((value >> shift) & 0xFF) as f32 / 255.0;
+        Self::rgb(channel(16), channel(8), channel(0))
+    }
+
+    /// Get the same color with a different alpha.
+    pub fn with_alpha(self, a: f32) -> Self {
+        Self { a, ..self }
+    }
+}
+
+impl Default for Color {
+    fn default() -> Self {
+        Self::WHITE
+    }
+}
+
+impl From<(f32, f32, f32)> for Color {
+    fn from((r, g, b): (f32, f32, f32)) -> Self {
+        Self::rgb(r, g, b)
+    }
+}
+
+impl From<(f32, f32, f32, f32)> for Color {
+    fn from((r, g, b, a): (f32, f32, f32, f32)) -> Self {
+        Self::rgba(r, g, b, a)
+    }
+}
+
+impl From<Vector3<f32>> for Color {
+    fn from(color: Vector3<f32>) -> Self {
+        Self::rgb(color.x, color.y, color.z)
+    }
+}
+
+impl From<&Vector3<f32>> for Color {
+    fn from(color: &Vector3<f32>) -> Self {
+        Self::rgb(color.x, color.y, color.z)
+    }
+}
+
+impl From<Vector4<f32>> for Color {
+    fn from(color: Vector4<f32>) -> Self {
+        Self::rgba(color.x, color.y, color.z, color.w)
+    }
+}
+
+impl From<Color> for (f32, f32, f32, f32) {
+    fn from(color: Color) -> Self {
+        (color.r, color.g, color.b, color.a)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_splits_the_channels() {
+        assert_eq!(Color::hex(0xFF8000), Color::rgb(1.0, 128.0 / 255.0, 0.0));
+        assert_eq!(Color::hex(0x000000), Color::BLACK);
+        assert_eq!(Color::hex(0xFFFFFF), Color::WHITE);
+        // Anything above the blue, green and red bytes is ignored.
+        assert_eq!(Color::hex(0x12_0000FF), Color::rgb(0.0, 0.0, 1.0));
+    }
+}
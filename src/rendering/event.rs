@@ -0,0 +1,191 @@
+use nalgebra::Vector2;
+
+/// An input or window event, translated from the windowing backend.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+    /// The window was asked to close.
+    Quit,
+    KeyDown {
+        key: Key,
+        /// Whether this is a repeat from the key being held.
+        repeat: bool,
+    },
+    KeyUp {
+        key: Key,
+    },
+    /// Text typed by the user, respecting the keyboard layout and composed characters.
+    TextInput {
+        text: String,
+    },
+    /// The mouse moved, the position is in window pixels with a top-left origin.
+    MouseMove {
+        position: Vector2<i32>,
+        delta: Vector2<i32>,
+    },
+    MouseDown {
+        button: MouseButton,
+        position: Vector2<i32>,
+    },
+    MouseUp {
+        button: MouseButton,
+        position: Vector2<i32>,
+    },
+    /// The mouse wheel scrolled, positive y is up and positive x is right.
+    MouseWheel {
+        delta: Vector2<f32>,
+    },
+    /// The window changed size, in logical pixels.
+    Resized {
+        width: u32,
+        height: u32,
+    },
+    FocusGained,
+    FocusLost,
+    GamepadConnected {
+        id: u32,
+    },
+    GamepadDisconnected {
+        id: u32,
+    },
+}
+
+/// A key on the keyboard, by the symbol it produces in the current layout.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Key {
+    A,
+    B,
+    C,
+    D,
+    E,
+    F,
+    G,
+    H,
+    I,
+    J,
+    K,
+    L,
+    M,
+    N,
+    O,
+    P,
+    Q,
+    R,
+    S,
+    T,
+    U,
+    V,
+    W,
+    X,
+    Y,
+    Z,
+    Num0,
+    Num1,
+    Num2,
+    Num3,
+    Num4,
+    Num5,
+    Num6,
+    Num7,
+    Num8,
+    Num9,
+    F1,
+    F2,
+    F3,
+    F4,
+    F5,
+    F6,
+    F7,
+    F8,
+    F9,
+    F10,
+    F11,
+    F12,
+    Escape,
+    Enter,
+    Space,
+    Tab,
+    Backspace,
+    Delete,
+    Insert,
+    Home,
+    End,
+    PageUp,
+    PageDown,
+    Left,
+    Right,
+    Up,
+    Down,
+    LeftShift,
+    RightShift,
+    LeftCtrl,
+    RightCtrl,
+    LeftAlt,
+    RightAlt,
+    LeftSuper,
+    RightSuper,
+    CapsLock,
+    Minus,
+    Equals,
+    LeftBracket,
+    RightBracket,
+    Backslash,
+    Semicolon,
+    Quote,
+    Backquote,
+    Comma,
+    Period,
+    Slash,
+    /// A key without a mapping.
+    Unknown,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum MouseButton {
+    Left,
+    Middle,
+    Right,
+    X1,
+    X2,
+}
+
+/// A gamepad button, named by its position on an Xbox style controller.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum GamepadButton {
+    /// The bottom face button.
+    A,
+    /// The right face button.
+    B,
+    /// The left face button.
+    X,
+    /// The top face button.
+    Y,
+    Back,
+    Guide,
+    Start,
+    LeftStick,
+    RightStick,
+    LeftShoulder,
+    RightShoulder,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+    /// Share, capture or microphone buttons, depending on the controller.
+    Misc,
+    Paddle1,
+    Paddle2,
+    Paddle3,
+    Paddle4,
+    Touchpad,
+}
+
+/// A gamepad stick or trigger axis.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum GamepadAxis {
+    LeftX,
+    LeftY,
+    RightX,
+    RightY,
+    LeftTrigger,
+    RightTrigger,
+}
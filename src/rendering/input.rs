@@ -0,0 +1,159 @@
+use std::collections::HashSet;
+
+use nalgebra::Vector2;
+
+use crate::rendering::{Event, Key};
+
+/// Keyboard state built up from the window events, updated once per frame.
+#[derive(Debug, Default)]
+pub struct Input {
+    down: HashSet<Key>,
+    pressed: HashSet<Key>,
+    released: HashSet<Key>,
+    mouse_delta: Vector2<i32>,
+    scroll_delta: Vector2<f32>,
+}
+
+impl Input {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a new frame, clearing the keys pressed and released during the last one.
+    pub fn begin_frame(&mut self) {
+        self.pressed.clear();
+        self.released.clear();
+        self.mouse_delta = Vector2::zeros();
+        self.scroll_delta = Vector2::zeros();
+    }
+
+    pub fn handle_event(&mut self, event: &Event) {
+        match event {
+            Event::KeyDown { key, repeat: false } => {
+                self.down.insert(*key);
+                self.pressed.insert(*key);
+            }
+            Event::KeyUp { key } => {
+                self.down.remove(key);
+                self.released.insert(*key);
+            }
+            Event::MouseMove { delta, .. } => {
+                self.mouse_delta += delta;
+            }
+            Event::MouseWheel { delta } => {
+                self.scroll_delta += delta;
+            }
+            // Key releases are missed while the window is unfocused.
+            Event::FocusLost => {
+                self.released.extend(self.down.drain());
+            }
+            _ => {}
+        }
+    }
+
+    /// Whether the key is currently held.
+    pub fn is_down(&self, key: Key) -> bool {
+        self.down.contains(&key)
+    }
+
+    /// Whether the key was pressed this frame, ignoring key repeats.
+    pub fn just_pressed(&self, key: Key) -> bool {
+        self.pressed.contains(&key)
+    }
+
+    /// Whether the key was released this frame.
+    pub fn just_released(&self, key: Key) -> bool {
+        self.released.contains(&key)
+    }
+
+    /// How far the mouse moved this frame in window pixels, also reported in relative mouse mode.
+    pub fn mouse_delta(&self) -> Vector2<i32> {
+        self.mouse_delta
+    }
+
+    /// How far the mouse wheel scrolled this frame, positive y is up and positive x is right.
+    ///
+    /// Trackpads report horizontal scrolling on x.
+    pub fn scroll_delta(&self) -> Vector2<f32> {
+        self.scroll_delta
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key_down(input: &mut Input, key: Key, repeat: bool) {
+        input.handle_event(&Event::KeyDown { key, repeat });
+    }
+
+    #[test]
+    fn presses_and_releases_last_one_frame() {
+        let mut input = Input::new();
+
+        input.begin_frame();
+        key_down(&mut input, Key::Space, false);
+        assert!(input.just_pressed(Key::Space));
+        assert!(input.is_down(Key::Space));
+
+        // Held across frames, repeats don't count as presses.
+        input.begin_frame();
+        key_down(&mut input, Key::Space, true);
+        assert!(!input.just_pressed(Key::Space));
+        assert!(input.is_down(Key::Space));
+
+        input.begin_frame();
+        input.handle_event(&Event::KeyUp { key: Key::Space });
+        assert!(input.just_released(Key::Space));
+        assert!(!input.is_down(Key::Space));
+
+        input.begin_frame();
+        assert!(!input.just_released(Key::Space));
+    }
+
+    #[test]
+    fn tapping_within_a_frame_is_both_pressed_and_released() {
+        let mut input = Input::new();
+
+        input.begin_frame();
+        key_down(&mut input, Key::A, false);
+        input.handle_event(&Event::KeyUp { key: Key::A });
+
+        assert!(input.just_pressed(Key::A));
+        assert!(input.just_released(Key::A));
+        assert!(!input.is_down(Key::A));
+    }
+
+    #[test]
+    fn losing_focus_releases_held_keys() {
+        let mut input = Input::new();
+        key_down(&mut input, Key::A, false);
+
+        input.begin_frame();
+        input.handle_event(&Event::FocusLost);
+
+        assert!(input.just_released(Key::A));
+        assert!(!input.is_down(Key::A));
+    }
+
+    #[test]
+    fn deltas_accumulate_within_a_frame() {
+        let mut input = Input::new();
+
+        for delta in [Vector2::new(3, -1), Vector2::new(2, 4)] {
+            input.handle_event(&Event::MouseMove {
+                position: Vector2::zeros(),
+                delta,
+            });
+        }
+        input.handle_event(&Event::MouseWheel {
+            delta: Vector2::new(0.0, 1.5),
+        });
+        assert_eq!(input.mouse_delta(), Vector2::new(5, 3));
+        assert_eq!(input.scroll_delta(), Vector2::new(0.0, 1.5));
+
+        input.begin_frame();
+        assert_eq!(input.mouse_delta(), Vector2::zeros());
+        assert_eq!(input.scroll_delta(), Vector2::zeros());
+    }
+}
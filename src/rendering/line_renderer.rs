@@ -0,0 +1,22 @@
+use nalgebra::{Matrix4, Vector2};
+
+use crate::{error::Error, rendering::Color};
+
+/// Accumulates lines for debug overlays and draws them all in a single draw call.
+pub trait LineRendererTrait {
+    fn new() -> Result<Self, Error>
+    where
+        Self: Sized;
+
+    /// Set the width of the lines in pixels, applies to the next flush.
+    ///
+    /// Core profile drivers are only required to support a width of `1.0`.
+    fn set_line_width(&mut self, width: f32);
+    fn line_width(&self) -> f32;
+
+    /// Add a line to be drawn on the next flush.
+    fn line(&self, start: Vector2<f32>, end: Vector2<f32>, color: impl Into<Color>);
+
+    /// Draw all of the accumulated lines and clear them.
+    fn flush(&self, projection: &Matrix4<f32>, view: &Matrix4<f32>) -> Result<(), Error>;
+}
@@ -1,11 +1,39 @@
+pub mod animation;
+pub mod bitmap_font;
+pub mod blend_mode;
 pub mod camera;
+pub mod color;
+pub mod event;
+pub mod input;
+pub mod line_renderer;
+pub mod rect;
+pub mod renderer;
+pub mod resources;
+pub mod shape_renderer;
 pub mod sprite_renderer;
 pub mod texture;
+pub mod texture_atlas;
+pub mod tile_map;
+pub mod transform;
 pub mod window;
 
+pub use animation::*;
+pub use bitmap_font::*;
+pub use blend_mode::*;
 pub use camera::*;
+pub use color::*;
+pub use event::*;
+pub use input::*;
+pub use line_renderer::*;
+pub use rect::*;
+pub use renderer::*;
+pub use resources::*;
+pub use shape_renderer::*;
 pub use sprite_renderer::*;
 pub use texture::*;
+pub use texture_atlas::*;
+pub use tile_map::*;
+pub use transform::*;
 pub use window::*;
 
 // OpenGL renderering.
@@ -14,6 +42,10 @@ pub mod opengl;
 #[cfg(feature = "opengl")]
 pub use opengl::*;
 
+// The backend used when not picking one explicitly.
+#[cfg(feature = "opengl")]
+pub type DefaultRenderer = opengl::OpenGlRenderer;
+
 // SDL2 windowing / input layer.
 #[cfg(feature = "sdl2")]
 pub mod sdl2;
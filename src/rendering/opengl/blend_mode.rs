@@ -0,0 +1,27 @@
+use crate::{gl, rendering::BlendMode, utils::GlError};
+
+impl BlendMode {
+    /// The source and destination factors passed to `glBlendFunc`.
+    pub fn gl_blend_func(&self) -> (u32, u32) {
+        match self {
+            BlendMode::Alpha => (gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA),
+            BlendMode::Additive => (gl::SRC_ALPHA, gl::ONE),
+            BlendMode::Multiply => (gl::DST_COLOR, gl::ZERO),
+            BlendMode::Premultiplied => (gl::ONE, gl::ONE_MINUS_SRC_ALPHA),
+        }
+    }
+}
+
+/// Enable blending with the given mode.
+pub(crate) fn enable_blending(mode: BlendMode) -> Result<(), GlError> {
+    let (source, destination) = mode.gl_blend_func();
+    gl!(Enable, gl::BLEND)?;
+    gl!(BlendFunc, source, destination)
+}
+
+/// Disable blending and restore the default blend function.
+pub(crate) fn disable_blending() -> Result<(), GlError> {
+    let (source, destination) = BlendMode::default().gl_blend_func();
+    gl!(BlendFunc, source, destination)?;
+    gl!(Disable, gl::BLEND)
+}
@@ -0,0 +1,147 @@
+use std::cell::Cell;
+
+use nalgebra::{Matrix4, Vector2};
+
+use super::{
+    blend_mode::{disable_blending, enable_blending},
+    GlState, Shader, ShaderProgram, ShaderType,
+};
+use crate::{
+    error::Error,
+    gl, gl_unchecked,
+    rendering::{BlendMode, Color},
+};
+
+// Per vertex data, a vec2 position followed by a vec4 color.
+pub(crate) const VERTEX_FLOATS: usize = 2 + 4;
+
+/// Append a vertex in the layout used by `ColoredBuffer`.
+pub(crate) fn push_vertex(vertices: &mut Vec<f32>, position: Vector2<f32>, color: Color) {
+    vertices.extend_from_slice(&[position.x, position.y, color.r, color.g, color.b, color.a]);
+}
+
+/// A dynamic vertex buffer of untextured colored vertices, drawn with the colored shader.
+///
+/// Shared by the shape and line renderers.
+pub(crate) struct ColoredBuffer {
+    shader: ShaderProgram,
+    vao: u32,
+    vbo: u32,
+    capacity: Cell<usize>,
+}
+
+impl ColoredBuffer {
+    pub fn new() -> Result<Self, Error> {
+        let shader = ShaderProgram::from_shaders(&[
+            Shader::from_source(
+                ShaderType::Vertex,
+                include_str!("../../../assets/shaders/colored.vert"),
+            )?,
+            Shader::from_source(
+                ShaderType::Fragment,
+                include_str!("../../../assets/shaders/colored.frag"),
+            )?,
+        ])?;
+
+        let mut vao = 0;
+        let mut vbo = 0;
+
+        gl!(GenVertexArrays, 1, &mut vao)?;
+        GlState::bind_vertex_array(vao)?;
+        gl!(GenBuffers, 1, &mut vbo)?;
+        gl!(BindBuffer, gl::ARRAY_BUFFER, vbo)?;
+
+        let stride = (VERTEX_FLOATS * std::mem::size_of::<f32>()) as i32;
+        gl!(
+            VertexAttribPointer,
+            0,
+            2,
+            gl::FLOAT,
+            gl::FALSE,
+            stride,
+            std::ptr::null(),
+        )?;
+        gl!(EnableVertexAttribArray, 0)?;
+        gl!(
+            VertexAttribPointer,
+            1,
+            4,
+            gl::FLOAT,
+            gl::FALSE,
+            stride,
+            (2 * std::mem::size_of::<f32>()) as *const _,
+        )?;
+        gl!(EnableVertexAttribArray, 1)?;
+
+        gl!(BindBuffer, gl::ARRAY_BUFFER, 0)?;
+        GlState::bind_vertex_array(0)?;
+
+        Ok(Self {
+            shader,
+            vao,
+            vbo,
+            capacity: Cell::new(0),
+        })
+    }
+
+    /// Upload the vertices and draw them as `mode` primitives in a single draw call.
+    pub fn draw(
+        &self,
+        mode: u32,
+        view_projection: &Matrix4<f32>,
+        vertices: &[f32],
+    ) -> Result<(), Error> {
+        let count = vertices.len() / VERTEX_FLOATS;
+        if count == 0 {
+            return Ok(());
+        }
+
+        enable_blending(BlendMode::Alpha)?;
+
+        self.shader.use_program();
+        self.shader
+            .set_uniform_mat4("viewProjection", view_projection)?;
+
+        // Only reallocate the vertex buffer when it needs to grow.
+        gl!(BindBuffer, gl::ARRAY_BUFFER, self.vbo)?;
+        if count > self.capacity.get() {
+            gl!(
+                BufferData,
+                gl::ARRAY_BUFFER,
+                std::mem::size_of_val(vertices) as isize,
+                vertices.as_ptr() as *const _,
+                gl::STREAM_DRAW,
+            )?;
+            self.capacity.set(count);
+        } else {
+            gl!(
+                BufferSubData,
+                gl::ARRAY_BUFFER,
+                0,
+                std::mem::size_of_val(vertices) as isize,
+                vertices.as_ptr() as *const _,
+            )?;
+        }
+        gl!(BindBuffer, gl::ARRAY_BUFFER, 0)?;
+
+        GlState::bind_vertex_array(self.vao)?;
+        gl!(DrawArrays, mode, 0, count as i32)?;
+
+        disable_blending()?;
+
+        Ok(())
+    }
+}
+
+impl Drop for ColoredBuffer {
+    fn drop(&mut self) {
+        // Nothing to free if GL was never loaded.
+        if !gl::DeleteVertexArrays::is_loaded() {
+            return;
+        }
+
+        GlState::forget_vertex_array(self.vao);
+        gl_unchecked!(DeleteVertexArrays, 1, &self.vao);
+        gl_unchecked!(DeleteBuffers, 1, &self.vbo);
+    }
+}
@@ -0,0 +1,142 @@
+use std::{
+    cell::{Cell, RefCell},
+    ffi::CStr,
+    os::raw::{c_char, c_void},
+};
+
+use crate::{error::Error, gl};
+
+/// How important a driver debug message is, ordered from least to most severe.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DebugSeverity {
+    Notification,
+    Low,
+    Medium,
+    High,
+}
+
+impl DebugSeverity {
+    fn from_gl(severity: u32) -> Self {
+        match severity {
+            gl::DEBUG_SEVERITY_HIGH => DebugSeverity::High,
+            gl::DEBUG_SEVERITY_MEDIUM => DebugSeverity::Medium,
+            gl::DEBUG_SEVERITY_LOW => DebugSeverity::Low,
+            _ => DebugSeverity::Notification,
+        }
+    }
+}
+
+/// A message reported by the driver through `KHR_debug`.
+#[derive(Debug)]
+pub struct DebugMessage<'a> {
+    /// The `GL_DEBUG_SOURCE_*` the message came from.
+    pub source: u32,
+    /// The `GL_DEBUG_TYPE_*` of the message, e.g. deprecated behavior or a performance hint.
+    pub kind: u32,
+    pub id: u32,
+    pub severity: DebugSeverity,
+    pub message: &'a str,
+}
+
+struct DebugOutput {
+    min_severity: DebugSeverity,
+    callback: Box<dyn Fn(&DebugMessage)>,
+}
+
+thread_local! {
+    // Messages are reported synchronously, so they arrive on the thread owning the context.
+    static DEBUG_OUTPUT: RefCell<Option<DebugOutput>> = const { RefCell::new(None) };
+    // Set while the user callback runs, so GL calls made from it can't report back into it.
+    static IN_CALLBACK: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Route driver debug messages of at least `min_severity` into `callback`.
+///
+/// Requires a debug context with `KHR_debug` support (core since OpenGL 4.3), replaces any
+/// previously installed callback.
+pub fn enable_debug_output(
+    min_severity: DebugSeverity,
+    callback: impl Fn(&DebugMessage) + 'static,
+) -> Result<(), Error> {
+    if !gl::DebugMessageCallback::is_loaded() {
+        return Err(Error::Unsupported {
+            message: "debug output requires KHR_debug".to_string(),
+        });
+    }
+
+    DEBUG_OUTPUT.with(|output| {
+        *output.borrow_mut() = Some(DebugOutput {
+            min_severity,
+            callback: Box::new(callback),
+        })
+    });
+
+    gl!(Enable, gl::DEBUG_OUTPUT)?;
+    // Report messages on the calling thread, during the call that caused them.
+    gl!(Enable, gl::DEBUG_OUTPUT_SYNCHRONOUS)?;
+    gl!(DebugMessageCallback, Some(debug_callback), std::ptr::null())?;
+
+    Ok(())
+}
+
+/// Stop routing driver debug messages and drop the installed callback.
+pub fn disable_debug_output() -> Result<(), Error> {
+    if gl::DebugMessageCallback::is_loaded() {
+        gl!(DebugMessageCallback, None, std::ptr::null())?;
+        gl!(Disable, gl::DEBUG_OUTPUT)?;
+    }
+
+    DEBUG_OUTPUT.with(|output| *output.borrow_mut() = None);
+
+    Ok(())
+}
+
+extern "system" fn debug_callback(
+    source: u32,
+    kind: u32,
+    id: u32,
+    severity: u32,
+    length: i32,
+    message: *const c_char,
+    _user_param: *mut c_void,
+) {
+    if message.is_null() {
+        return;
+    }
+
+    let message = if length >= 0 {
+        let bytes = unsafe { std::slice::from_raw_parts(message as *const u8, length as usize) };
+        String::from_utf8_lossy(bytes)
+    } else {
+        unsafe { CStr::from_ptr(message) }.to_string_lossy()
+    };
+
+    let severity = DebugSeverity::from_gl(severity);
+
+    if IN_CALLBACK.get() {
+        return;
+    }
+
+    DEBUG_OUTPUT.with(|output| {
+        // Shared borrows don't conflict, so this doesn't catch re-entrant calls, `IN_CALLBACK`
+        // does. It only skips messages while the output is mutably borrowed.
+        let output = match output.try_borrow() {
+            Ok(output) => output,
+            Err(_) => return,
+        };
+
+        if let Some(output) = output.as_ref() {
+            if severity >= output.min_severity {
+                IN_CALLBACK.set(true);
+                (output.callback)(&DebugMessage {
+                    source,
+                    kind,
+                    id,
+                    severity,
+                    message: message.trim_end_matches('\0'),
+                });
+                IN_CALLBACK.set(false);
+            }
+        }
+    });
+}
@@ -0,0 +1,139 @@
+use std::cell::Cell;
+
+use super::Texture;
+use crate::{error::Error, gl, gl_unchecked, utils::GlError};
+
+/// An offscreen render target with a color texture attachment.
+///
+/// While bound all drawing renders into the texture instead of the window.
+pub struct Framebuffer {
+    id: u32,
+    texture: Texture,
+    depth_renderbuffer: Option<u32>,
+    previous_viewport: Cell<[i32; 4]>,
+}
+
+impl Framebuffer {
+    /// Create a framebuffer, optionally with a depth and stencil buffer for depth testing
+    /// and masking.
+    pub fn new(width: u32, height: u32, depth: bool) -> Result<Self, Error> {
+        let texture = Texture::new(
+            width,
+            height,
+            gl::RGBA,
+            gl::RGBA,
+            gl::CLAMP_TO_EDGE,
+            gl::CLAMP_TO_EDGE,
+            gl::LINEAR,
+            gl::LINEAR,
+        )?;
+
+        let mut id = 0;
+        gl!(GenFramebuffers, 1, &mut id)?;
+        gl!(BindFramebuffer, gl::FRAMEBUFFER, id)?;
+        gl!(
+            FramebufferTexture2D,
+            gl::FRAMEBUFFER,
+            gl::COLOR_ATTACHMENT0,
+            gl::TEXTURE_2D,
+            texture.id(),
+            0
+        )?;
+
+        let depth_renderbuffer = if depth {
+            let mut renderbuffer = 0;
+            gl!(GenRenderbuffers, 1, &mut renderbuffer)?;
+            gl!(BindRenderbuffer, gl::RENDERBUFFER, renderbuffer)?;
+            gl!(
+                RenderbufferStorage,
+                gl::RENDERBUFFER,
+                gl::DEPTH24_STENCIL8,
+                width as i32,
+                height as i32
+            )?;
+            gl!(
+                FramebufferRenderbuffer,
+                gl::FRAMEBUFFER,
+                gl::DEPTH_STENCIL_ATTACHMENT,
+                gl::RENDERBUFFER,
+                renderbuffer
+            )?;
+            gl!(BindRenderbuffer, gl::RENDERBUFFER, 0)?;
+            Some(renderbuffer)
+        } else {
+            None
+        };
+
+        let status = gl!(CheckFramebufferStatus, gl::FRAMEBUFFER)?;
+        gl!(BindFramebuffer, gl::FRAMEBUFFER, 0)?;
+
+        let framebuffer = Self {
+            id,
+            texture,
+            depth_renderbuffer,
+            previous_viewport: Cell::new([0; 4]),
+        };
+
+        if status != gl::FRAMEBUFFER_COMPLETE {
+            return Err(Error::IncompleteFramebuffer { status });
+        }
+
+        Ok(framebuffer)
+    }
+
+    /// Render into the framebuffer, setting the viewport to cover the whole texture.
+    pub fn bind(&self) -> Result<(), GlError> {
+        let mut viewport = [0; 4];
+        gl!(GetIntegerv, gl::VIEWPORT, viewport.as_mut_ptr())?;
+        self.previous_viewport.set(viewport);
+
+        gl!(BindFramebuffer, gl::FRAMEBUFFER, self.id)?;
+        gl!(
+            Viewport,
+            0,
+            0,
+            self.texture.width() as i32,
+            self.texture.height() as i32
+        )
+    }
+
+    /// Go back to rendering into the window, restoring the viewport from before `bind`.
+    pub fn unbind(&self) -> Result<(), GlError> {
+        let [x, y, width, height] = self.previous_viewport.get();
+
+        gl!(BindFramebuffer, gl::FRAMEBUFFER, 0)?;
+        gl!(Viewport, x, y, width, height)
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// The texture holding the rendered color output.
+    ///
+    /// GL stores the rows bottom-up, so draw it flipped vertically to show it upright.
+    pub fn texture(&self) -> &Texture {
+        &self.texture
+    }
+
+    pub fn width(&self) -> u32 {
+        self.texture.width()
+    }
+
+    pub fn height(&self) -> u32 {
+        self.texture.height()
+    }
+}
+
+impl Drop for Framebuffer {
+    fn drop(&mut self) {
+        if !gl::DeleteFramebuffers::is_loaded() {
+            return;
+        }
+
+        if let Some(renderbuffer) = self.depth_renderbuffer {
+            gl_unchecked!(DeleteRenderbuffers, 1, &renderbuffer);
+        }
+        gl_unchecked!(DeleteFramebuffers, 1, &self.id);
+    }
+}
@@ -0,0 +1,114 @@
+use std::cell::Cell;
+
+use crate::{gl, utils::GlError};
+
+// Binds to higher units than this aren't cached.
+const TEXTURE_UNITS: usize = 16;
+
+/// The GL objects last bound by babo, so binding the same object again can be skipped.
+///
+/// Only binds made through `GlState` are tracked, call `reset` after binding programs,
+/// vertex arrays or textures with raw GL calls so the next bind isn't skipped.
+pub struct GlState {
+    program: Cell<Option<u32>>,
+    vertex_array: Cell<Option<u32>>,
+    active_texture: Cell<Option<u32>>,
+    textures: [Cell<Option<u32>>; TEXTURE_UNITS],
+}
+
+thread_local! {
+    // A GL context is only current on one thread, so its state is per thread too.
+    static STATE: GlState = const {
+        GlState {
+            program: Cell::new(None),
+            vertex_array: Cell::new(None),
+            active_texture: Cell::new(None),
+            textures: [const { Cell::new(None) }; TEXTURE_UNITS],
+        }
+    };
+}
+
+impl GlState {
+    /// `glUseProgram`, unless the program is already in use.
+    pub fn use_program(id: u32) -> Result<(), GlError> {
+        STATE.with(|state| bind(&state.program, id, || gl!(UseProgram, id)))
+    }
+
+    /// `glBindVertexArray`, unless the vertex array is already bound.
+    pub fn bind_vertex_array(id: u32) -> Result<(), GlError> {
+        STATE.with(|state| bind(&state.vertex_array, id, || gl!(BindVertexArray, id)))
+    }
+
+    /// Bind a 2D texture to a texture unit, skipping the `glActiveTexture` and
+    /// `glBindTexture` calls that wouldn't change anything.
+    pub fn bind_texture(unit: u32, id: u32) -> Result<(), GlError> {
+        STATE.with(|state| {
+            bind(&state.active_texture, unit, || {
+                gl!(ActiveTexture, gl::TEXTURE0 + unit)
+            })?;
+
+            match state.textures.get(unit as usize) {
+                Some(texture) => bind(texture, id, || gl!(BindTexture, gl::TEXTURE_2D, id)),
+                None => gl!(BindTexture, gl::TEXTURE_2D, id),
+            }
+        })
+    }
+
+    /// Forget everything that was bound, the next binds always call into GL.
+    ///
+    /// Has to be called whenever a different context is made current on this thread, the
+    /// cache only tracks one context. Creating a window does this already.
+    pub fn reset() {
+        STATE.with(|state| {
+            state.program.set(None);
+            state.vertex_array.set(None);
+            state.active_texture.set(None);
+            for texture in &state.textures {
+                texture.set(None);
+            }
+        });
+    }
+
+    /// Forget a program that is being deleted, so a new program reusing its id is bound.
+    pub(crate) fn forget_program(id: u32) {
+        STATE.with(|state| forget(&state.program, id, None));
+    }
+
+    /// Deleting a bound vertex array binds 0 in its place.
+    pub(crate) fn forget_vertex_array(id: u32) {
+        STATE.with(|state| forget(&state.vertex_array, id, Some(0)));
+    }
+
+    /// Deleting a bound texture binds 0 in its place, on every unit it was bound to.
+    pub(crate) fn forget_texture(id: u32) {
+        STATE.with(|state| {
+            for texture in &state.textures {
+                forget(texture, id, Some(0));
+            }
+        });
+    }
+}
+
+/// Run `bind` and remember `id`, unless `id` is already the bound object.
+fn bind(
+    cached: &Cell<Option<u32>>,
+    id: u32,
+    bind: impl FnOnce() -> Result<(), GlError>,
+) -> Result<(), GlError> {
+    if cached.get() == Some(id) {
+        return Ok(());
+    }
+
+    // Don't trust the cache if the bind failed.
+    cached.set(None);
+    bind()?;
+    cached.set(Some(id));
+
+    Ok(())
+}
+
+fn forget(cached: &Cell<Option<u32>>, id: u32, replacement: Option<u32>) {
+    if cached.get() == Some(id) {
+        cached.set(replacement);
+    }
+}
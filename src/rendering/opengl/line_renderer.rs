@@ -0,0 +1,64 @@
+use std::cell::RefCell;
+
+use nalgebra::{Matrix4, Vector2};
+
+use super::colored_buffer::{push_vertex, ColoredBuffer};
+use crate::{
+    error::Error,
+    gl,
+    rendering::{Color, LineRendererTrait},
+};
+
+/// Renders batches of lines with `GL_LINES`.
+///
+/// The GL objects are deleted when the renderer is dropped, so it has to be dropped
+/// before the window that owns the GL context.
+pub struct LineRenderer {
+    buffer: ColoredBuffer,
+    vertices: RefCell<Vec<f32>>,
+    line_width: f32,
+}
+
+impl LineRendererTrait for LineRenderer {
+    fn new() -> Result<Self, Error> {
+        Ok(Self {
+            buffer: ColoredBuffer::new()?,
+            vertices: RefCell::new(Vec::new()),
+            line_width: 1.0,
+        })
+    }
+
+    fn set_line_width(&mut self, width: f32) {
+        self.line_width = width;
+    }
+
+    fn line_width(&self) -> f32 {
+        self.line_width
+    }
+
+    fn line(&self, start: Vector2<f32>, end: Vector2<f32>, color: impl Into<Color>) {
+        let color = color.into();
+        let mut vertices = self.vertices.borrow_mut();
+
+        push_vertex(&mut vertices, start, color);
+        push_vertex(&mut vertices, end, color);
+    }
+
+    fn flush(&self, projection: &Matrix4<f32>, view: &Matrix4<f32>) -> Result<(), Error> {
+        let mut vertices = self.vertices.borrow_mut();
+
+        // Core profiles reject widths above 1.0, still draw and clear the lines when they do.
+        let width = gl!(LineWidth, self.line_width);
+        let result = self.buffer.draw(gl::LINES, &(projection * view), &vertices);
+        let reset = gl!(LineWidth, 1.0);
+
+        // Clear the lines even if drawing failed, keeping the allocation for the next frame.
+        vertices.clear();
+
+        width?;
+        result?;
+        reset?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,185 @@
+use super::GlState;
+use crate::{error::Error, gl, gl_unchecked, utils::GlError};
+
+/// Geometry on the GPU, a vertex buffer with an optional index buffer.
+///
+/// Vertices are interleaved floats, `layout` gives the number of components of each
+/// attribute, bound to consecutive locations starting at 0.
+///
+/// The GL objects are deleted when the mesh is dropped, so it has to be dropped before
+/// the window that owns the GL context.
+pub struct Mesh {
+    vao: u32,
+    vbo: u32,
+    ebo: Option<u32>,
+    layout: Vec<i32>,
+    vertex_count: i32,
+    index_count: i32,
+    mode: u32,
+}
+
+impl Mesh {
+    /// Upload a mesh of triangles, drawn with `glDrawElements` when `indices` are given.
+    pub fn new(vertices: &[f32], layout: &[i32], indices: Option<&[u32]>) -> Result<Self, Error> {
+        let stride: i32 = layout.iter().sum();
+        if stride == 0 || !vertices.len().is_multiple_of(stride as usize) {
+            return Err(Error::InvalidArgument {
+                message: format!(
+                    "{} floats of vertex data don't fit a layout of {} floats per vertex",
+                    vertices.len(),
+                    stride
+                ),
+            });
+        }
+
+        let mut vao = 0;
+        let mut vbo = 0;
+
+        gl!(GenVertexArrays, 1, &mut vao)?;
+        GlState::bind_vertex_array(vao)?;
+
+        gl!(GenBuffers, 1, &mut vbo)?;
+        gl!(BindBuffer, gl::ARRAY_BUFFER, vbo)?;
+        gl!(
+            BufferData,
+            gl::ARRAY_BUFFER,
+            std::mem::size_of_val(vertices) as isize,
+            vertices.as_ptr() as *const _,
+            gl::STATIC_DRAW,
+        )?;
+
+        let ebo = match indices {
+            Some(indices) => {
+                let mut ebo = 0;
+                gl!(GenBuffers, 1, &mut ebo)?;
+                gl!(BindBuffer, gl::ELEMENT_ARRAY_BUFFER, ebo)?;
+                gl!(
+                    BufferData,
+                    gl::ELEMENT_ARRAY_BUFFER,
+                    std::mem::size_of_val(indices) as isize,
+                    indices.as_ptr() as *const _,
+                    gl::STATIC_DRAW,
+                )?;
+                Some(ebo)
+            }
+            None => None,
+        };
+
+        let mesh = Self {
+            vao,
+            vbo,
+            ebo,
+            layout: layout.to_vec(),
+            vertex_count: vertices.len() as i32 / stride,
+            index_count: indices.map_or(0, |indices| indices.len() as i32),
+            mode: gl::TRIANGLES,
+        };
+
+        mesh.setup_attributes()?;
+
+        // Unbind the VAO first, it holds on to the element buffer binding.
+        GlState::bind_vertex_array(0)?;
+        gl!(BindBuffer, gl::ARRAY_BUFFER, 0)?;
+        gl!(BindBuffer, gl::ELEMENT_ARRAY_BUFFER, 0)?;
+
+        Ok(mesh)
+    }
+
+    /// Bind the mesh buffers and vertex attributes to the currently bound vertex array.
+    ///
+    /// Used to share the geometry with another vertex array, e.g. one with extra per
+    /// instance attributes.
+    pub fn setup_vertex_array(&self) -> Result<(), GlError> {
+        gl!(BindBuffer, gl::ARRAY_BUFFER, self.vbo)?;
+        if let Some(ebo) = self.ebo {
+            gl!(BindBuffer, gl::ELEMENT_ARRAY_BUFFER, ebo)?;
+        }
+
+        self.setup_attributes()
+    }
+
+    fn setup_attributes(&self) -> Result<(), GlError> {
+        let float_size = std::mem::size_of::<f32>() as i32;
+        let stride = self.layout.iter().sum::<i32>() * float_size;
+
+        let mut offset = 0;
+        for (location, &components) in self.layout.iter().enumerate() {
+            gl!(
+                VertexAttribPointer,
+                location as u32,
+                components,
+                gl::FLOAT,
+                gl::FALSE,
+                stride,
+                (offset * float_size) as usize as *const _,
+            )?;
+            gl!(EnableVertexAttribArray, location as u32)?;
+            offset += components;
+        }
+
+        Ok(())
+    }
+
+    pub fn draw(&self) -> Result<(), GlError> {
+        GlState::bind_vertex_array(self.vao)?;
+        self.draw_bound(1)
+    }
+
+    /// Issue the draw call for `instances` copies of the mesh with the currently bound
+    /// vertex array, which has to be set up with `setup_vertex_array`.
+    pub fn draw_bound(&self, instances: i32) -> Result<(), GlError> {
+        match (self.ebo, instances) {
+            (Some(_), 1) => gl!(
+                DrawElements,
+                self.mode,
+                self.index_count,
+                gl::UNSIGNED_INT,
+                std::ptr::null()
+            ),
+            (Some(_), _) => gl!(
+                DrawElementsInstanced,
+                self.mode,
+                self.index_count,
+                gl::UNSIGNED_INT,
+                std::ptr::null(),
+                instances
+            ),
+            (None, 1) => gl!(DrawArrays, self.mode, 0, self.vertex_count),
+            (None, _) => gl!(
+                DrawArraysInstanced,
+                self.mode,
+                0,
+                self.vertex_count,
+                instances
+            ),
+        }
+    }
+
+    pub fn vao(&self) -> u32 {
+        self.vao
+    }
+
+    pub fn vertex_count(&self) -> i32 {
+        self.vertex_count
+    }
+
+    pub fn index_count(&self) -> i32 {
+        self.index_count
+    }
+}
+
+impl Drop for Mesh {
+    fn drop(&mut self) {
+        // Nothing to free if GL was never loaded.
+        if !gl::DeleteVertexArrays::is_loaded() {
+            return;
+        }
+
+        GlState::forget_vertex_array(self.vao);
+        gl_unchecked!(DeleteVertexArrays, 1, &self.vao);
+        gl_unchecked!(DeleteBuffers, 1, &self.vbo);
+        if let Some(ebo) = self.ebo {
+            gl_unchecked!(DeleteBuffers, 1, &ebo);
+        }
+    }
+}
@@ -1,7 +1,32 @@
+pub mod blend_mode;
+mod colored_buffer;
+pub mod debug;
+pub mod framebuffer;
+pub mod gl_state;
+pub mod line_renderer;
+pub mod mesh;
+pub mod post_process;
+mod preprocessor;
+pub mod quad_batch;
+pub mod renderer;
 pub mod shader;
+pub mod shape_renderer;
 pub mod sprite_renderer;
+pub mod stencil;
 pub mod texture;
+pub mod tile_map_renderer;
 
+pub use debug::*;
+pub use framebuffer::*;
+pub use gl_state::*;
+pub use line_renderer::*;
+pub use mesh::*;
+pub use post_process::*;
+pub use quad_batch::*;
+pub use renderer::*;
 pub use shader::*;
+pub use shape_renderer::*;
 pub use sprite_renderer::*;
+pub use stencil::*;
 pub use texture::*;
+pub use tile_map_renderer::*;
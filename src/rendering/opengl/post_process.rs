@@ -0,0 +1,155 @@
+use super::{Framebuffer, GlState, Shader, ShaderProgram, ShaderType};
+use crate::{error::Error, gl, gl_unchecked};
+
+/// A chain of full-screen shader passes applied to a rendered scene.
+///
+/// The scene is drawn into an offscreen framebuffer, then each pass samples the previous
+/// result from the `screenTexture` uniform, the last pass drawing into the window.
+pub struct PostProcess {
+    targets: [Framebuffer; 2],
+    passes: Vec<ShaderProgram>,
+    copy: ShaderProgram,
+    vao: u32,
+}
+
+impl PostProcess {
+    /// Create a post-processing chain with no passes, rendering at the given size in
+    /// drawable pixels.
+    pub fn new(width: u32, height: u32) -> Result<Self, Error> {
+        let copy = PostProcess::pass(Shader::from_source(
+            ShaderType::Fragment,
+            include_str!("../../../assets/shaders/post/copy.frag"),
+        )?)?;
+
+        // The full-screen triangle is generated in the vertex shader, but core profiles
+        // still need a vertex array bound to draw.
+        let mut vao = 0;
+        gl!(GenVertexArrays, 1, &mut vao)?;
+
+        Ok(Self {
+            targets: PostProcess::create_targets(width, height)?,
+            passes: Vec::new(),
+            copy,
+            vao,
+        })
+    }
+
+    /// Link a fragment shader with the full-screen vertex shader, ready for `add_pass`.
+    ///
+    /// The fragment shader gets the `textureCoordinates` input and samples `screenTexture`.
+    pub fn pass(fragment: Shader) -> Result<ShaderProgram, Error> {
+        ShaderProgram::from_shaders(&[
+            Shader::from_source(
+                ShaderType::Vertex,
+                include_str!("../../../assets/shaders/post/fullscreen.vert"),
+            )?,
+            fragment,
+        ])
+    }
+
+    /// A built-in pass that converts the image to grayscale.
+    pub fn grayscale() -> Result<ShaderProgram, Error> {
+        PostProcess::pass(Shader::from_source(
+            ShaderType::Fragment,
+            include_str!("../../../assets/shaders/post/grayscale.frag"),
+        )?)
+    }
+
+    /// Add a pass to the end of the chain.
+    pub fn add_pass(&mut self, shader: ShaderProgram) {
+        self.passes.push(shader);
+    }
+
+    pub fn passes(&self) -> &[ShaderProgram] {
+        &self.passes
+    }
+
+    pub fn passes_mut(&mut self) -> &mut [ShaderProgram] {
+        &mut self.passes
+    }
+
+    /// Recreate the offscreen targets, call this when the window is resized.
+    pub fn resize(&mut self, width: u32, height: u32) -> Result<(), Error> {
+        self.targets = PostProcess::create_targets(width, height)?;
+        Ok(())
+    }
+
+    /// Render the scene drawn by `scene` through every pass and into the window.
+    ///
+    /// Depth and stencil testing are disabled while the passes draw and restored afterwards.
+    pub fn render<R>(&self, scene: impl FnOnce() -> R) -> Result<R, Error> {
+        // Only the scene target has depth and stencil, start it from a clean slate so the
+        // previous frame can't reject anything.
+        self.targets[0].bind()?;
+        gl!(Clear, gl::DEPTH_BUFFER_BIT | gl::STENCIL_BUFFER_BIT)?;
+        let result = scene();
+        self.targets[0].unbind()?;
+
+        // The passes draw a single full-screen triangle, so depth and stencil tests would
+        // only ever discard it. Remember what the scene enabled to restore it afterwards.
+        let depth_test = gl!(IsEnabled, gl::DEPTH_TEST)? == gl::TRUE;
+        let stencil_test = gl!(IsEnabled, gl::STENCIL_TEST)? == gl::TRUE;
+        gl!(Disable, gl::DEPTH_TEST)?;
+        gl!(Disable, gl::STENCIL_TEST)?;
+
+        let drawn = self.draw_passes();
+
+        if depth_test {
+            gl!(Enable, gl::DEPTH_TEST)?;
+        }
+        if stencil_test {
+            gl!(Enable, gl::STENCIL_TEST)?;
+        }
+
+        drawn.map(|_| result)
+    }
+
+    fn draw_passes(&self) -> Result<(), Error> {
+        gl!(Disable, gl::BLEND)?;
+        GlState::bind_vertex_array(self.vao)?;
+
+        // With no passes the scene is copied into the window unchanged.
+        let passes: Vec<&ShaderProgram> = match self.passes.len() {
+            0 => vec![&self.copy],
+            _ => self.passes.iter().collect(),
+        };
+
+        for (index, shader) in passes.iter().enumerate() {
+            let source = &self.targets[index % 2];
+            let target = &self.targets[(index + 1) % 2];
+            let last = index + 1 == passes.len();
+
+            if !last {
+                target.bind()?;
+            }
+
+            shader.use_program();
+            // Passes that don't sample the previous result optimize the uniform out.
+            let _ = shader.set_uniform_1i("screenTexture", 0);
+            GlState::bind_texture(0, source.texture().id())?;
+            gl!(DrawArrays, gl::TRIANGLES, 0, 3)?;
+
+            if !last {
+                target.unbind()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn create_targets(width: u32, height: u32) -> Result<[Framebuffer; 2], Error> {
+        Ok([
+            Framebuffer::new(width, height, true)?,
+            Framebuffer::new(width, height, false)?,
+        ])
+    }
+}
+
+impl Drop for PostProcess {
+    fn drop(&mut self) {
+        if gl::DeleteVertexArrays::is_loaded() {
+            GlState::forget_vertex_array(self.vao);
+            gl_unchecked!(DeleteVertexArrays, 1, &self.vao);
+        }
+    }
+}
@@ -0,0 +1,292 @@
+use std::path::{Path, PathBuf};
+
+use super::ShaderError;
+use crate::error::Error;
+
+/// GLSL source with `#include` directives inlined.
+pub(crate) struct PreprocessedSource {
+    pub source: String,
+    /// The files the source was built from, indexed by the GLSL source string number
+    /// used in the `#line` directives. The root file is always first.
+    pub files: Vec<PathBuf>,
+}
+
+/// Inline all `#include "file.glsl"` directives, resolved relative to the including file.
+///
+/// `#line` directives are inserted around included code so compile errors report the
+/// line in the original file, with the index of the file in `files` as the source string.
+pub(crate) fn preprocess(source: &str, location: &Path) -> Result<PreprocessedSource, Error> {
+    let mut preprocessed = PreprocessedSource {
+        source: String::with_capacity(source.len()),
+        files: vec![location.to_path_buf()],
+    };
+
+    let mut stack = Vec::new();
+    if let Ok(path) = location.canonicalize() {
+        stack.push(path);
+    }
+
+    inline_includes(source, location, 0, &mut stack, &mut preprocessed)?;
+
+    Ok(preprocessed)
+}
+
+fn inline_includes(
+    source: &str,
+    location: &Path,
+    file_index: usize,
+    stack: &mut Vec<PathBuf>,
+    preprocessed: &mut PreprocessedSource,
+) -> Result<(), Error> {
+    let directory = location.parent().unwrap_or_else(|| Path::new(""));
+    let mut in_comment = false;
+
+    for (index, line) in source.lines().enumerate() {
+        let include_error = |message: String| -> Error {
+            ShaderError::Include {
+                message: format!("{}:{}: {}", display_name(location), index + 1, message),
+            }
+            .into()
+        };
+
+        // Lines starting inside a block comment are never directives, and `//` comments
+        // don't start with `#include` so they're skipped already.
+        let commented = in_comment;
+        in_comment = ends_in_comment(line, in_comment);
+
+        let include = match line.trim_start().strip_prefix("#include") {
+            Some(include) if !commented => include.trim(),
+            _ => {
+                preprocessed.source.push_str(line);
+                preprocessed.source.push('\n');
+                continue;
+            }
+        };
+
+        let name = include
+            .strip_prefix('"')
+            .and_then(|include| include.strip_suffix('"'))
+            .ok_or_else(|| include_error("expected #include \"file\"".to_string()))?;
+
+        let path = directory.join(name);
+        let canonical = path
+            .canonicalize()
+            .map_err(|e| include_error(format!("{}: {}", name, e)))?;
+
+        if stack.contains(&canonical) {
+            return Err(include_error(format!(
+                "recursive #include of {}",
+                path.display()
+            )));
+        }
+
+        let included = std::fs::read_to_string(&path)?;
+
+        let included_index = preprocessed.files.len();
+        preprocessed.files.push(path.clone());
+
+        // Report errors in the included file relative to the start of it.
+        preprocessed
+            .source
+            .push_str(&format!("#line 1 {}\n", included_index));
+
+        stack.push(canonical);
+        inline_includes(&included, &path, included_index, stack, preprocessed)?;
+        stack.pop();
+
+        // Continue on the line after the include in the including file.
+        preprocessed
+            .source
+            .push_str(&format!("#line {} {}\n", index + 2, file_index));
+    }
+
+    Ok(())
+}
+
+/// Whether a `/* */` comment is still open at the end of `line`.
+fn ends_in_comment(line: &str, mut in_comment: bool) -> bool {
+    let mut rest = line;
+
+    loop {
+        if in_comment {
+            match rest.find("*/") {
+                Some(end) => {
+                    in_comment = false;
+                    rest = &rest[end + 2..];
+                }
+                None => return true,
+            }
+        } else {
+            let line_comment = rest.find("//").unwrap_or(rest.len());
+            match rest[..line_comment].find("/*") {
+                Some(start) => {
+                    in_comment = true;
+                    rest = &rest[start + 2..];
+                }
+                None => return false,
+            }
+        }
+    }
+}
+
+impl PreprocessedSource {
+    /// Rewrite a driver compile log to name the file each message came from.
+    ///
+    /// Drivers report locations as `<source string>:<line>` or `<source string>(<line>)`,
+    /// the line numbers are already correct thanks to the `#line` directives.
+    pub fn annotate_log(&self, log: &str) -> String {
+        log.trim_end_matches(|c: char| c == '\0' || c.is_whitespace())
+            .lines()
+            .map(|line| self.annotate_line(line))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn annotate_line(&self, line: &str) -> String {
+        let bytes = line.as_bytes();
+
+        for (start, _) in line.match_indices(|c: char| c.is_ascii_digit()) {
+            // Only match whole numbers.
+            if start > 0 && bytes[start - 1].is_ascii_alphanumeric() {
+                continue;
+            }
+
+            let end = start
+                + line[start..]
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(line.len() - start);
+
+            let followed_by_line = matches!(bytes.get(end), Some(b':') | Some(b'('))
+                && bytes.get(end + 1).is_some_and(u8::is_ascii_digit);
+            if !followed_by_line {
+                continue;
+            }
+
+            return match line[start..end]
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| self.files.get(index))
+            {
+                Some(file) => format!("{}{}{}", &line[..start], display_name(file), &line[end..]),
+                None => line.to_string(),
+            };
+        }
+
+        line.to_string()
+    }
+}
+
+/// The name used for a file in error messages, shaders compiled from strings have no path.
+pub(crate) fn display_name(path: &Path) -> String {
+    if path.as_os_str().is_empty() {
+        "<source>".to_string()
+    } else {
+        path.display().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Write `files` into a fresh directory under the system temp directory.
+    fn write_files(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let directory =
+            std::env::temp_dir().join(format!("babo-preprocessor-{}-{}", std::process::id(), name));
+        std::fs::create_dir_all(&directory).unwrap();
+        for (file, source) in files {
+            std::fs::write(directory.join(file), source).unwrap();
+        }
+        directory
+    }
+
+    #[test]
+    fn nested_includes_are_inlined_with_line_directives() {
+        let directory = write_files(
+            "nested",
+            &[
+                (
+                    "main.frag",
+                    "#version 330 core\n#include \"a.glsl\"\nvoid main() {}\n",
+                ),
+                ("a.glsl", "#include \"b.glsl\"\nfloat a;\n"),
+                ("b.glsl", "float b;\n"),
+            ],
+        );
+        let main = directory.join("main.frag");
+
+        let preprocessed = preprocess(&std::fs::read_to_string(&main).unwrap(), &main).unwrap();
+
+        assert_eq!(
+            preprocessed.source,
+            "#version 330 core\n\
+             #line 1 1\n\
+             #line 1 2\n\
+             float b;\n\
+             #line 2 1\n\
+             float a;\n\
+             #line 3 0\n\
+             void main() {}\n"
+        );
+        assert_eq!(
+            preprocessed.files,
+            vec![main, directory.join("a.glsl"), directory.join("b.glsl")]
+        );
+    }
+
+    #[test]
+    fn include_cycles_are_an_error() {
+        let directory = write_files(
+            "cycle",
+            &[
+                ("a.glsl", "#include \"b.glsl\"\n"),
+                ("b.glsl", "#include \"a.glsl\"\n"),
+            ],
+        );
+        let a = directory.join("a.glsl");
+
+        match preprocess(&std::fs::read_to_string(&a).unwrap(), &a) {
+            Err(Error::Shader {
+                source: ShaderError::Include { message },
+            }) => assert!(message.contains("recursive #include"), "{message}"),
+            Err(error) => panic!("unexpected error {error}"),
+            Ok(_) => panic!("accepted an include cycle"),
+        }
+    }
+
+    #[test]
+    fn commented_includes_are_skipped() {
+        let source = "// #include \"missing.glsl\"\n\
+                      /*\n\
+                      #include \"missing.glsl\"\n\
+                      */\n\
+                      float a;\n";
+
+        let preprocessed = preprocess(source, Path::new("")).unwrap();
+
+        assert_eq!(preprocessed.source, source);
+        assert_eq!(preprocessed.files.len(), 1);
+    }
+
+    #[test]
+    fn annotate_log_names_the_source_files() {
+        let preprocessed = PreprocessedSource {
+            source: String::new(),
+            files: vec![PathBuf::from("main.frag"), PathBuf::from("lighting.glsl")],
+        };
+
+        // Mesa and NVIDIA style locations, an unknown source string and unrelated numbers.
+        let log = "0:3(12): error: `x' undeclared\n\
+                   1(7) : error C0000: syntax error\n\
+                   5:2(1): error: unknown\n\
+                   vec4 has 4 components\0";
+
+        assert_eq!(
+            preprocessed.annotate_log(log),
+            "main.frag:3(12): error: `x' undeclared\n\
+             lighting.glsl(7) : error C0000: syntax error\n\
+             5:2(1): error: unknown\n\
+             vec4 has 4 components"
+        );
+    }
+}
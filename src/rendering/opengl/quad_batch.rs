@@ -0,0 +1,230 @@
+use nalgebra::{Matrix4, Point3};
+
+use super::{
+    blend_mode::{disable_blending, enable_blending},
+    GlState, Shader, ShaderProgram, ShaderType, Texture,
+};
+use crate::{
+    error::Error,
+    gl, gl_unchecked,
+    rendering::{BlendMode, Color},
+};
+
+// Per vertex data, a vec2 position, a vec2 texture coordinate and a vec4 color.
+const VERTEX_FLOATS: usize = 2 + 2 + 4;
+const QUAD_FLOATS: usize = 4 * VERTEX_FLOATS;
+
+// The corners of the unit quad and the order they're drawn in.
+const CORNERS: [(f32, f32); 4] = [(0.0, 0.0), (1.0, 0.0), (1.0, 1.0), (0.0, 1.0)];
+const CORNER_INDICES: [u32; 6] = [0, 1, 2, 0, 2, 3];
+
+/// Accumulates textured quads on the CPU and draws them in a single draw call per flush.
+///
+/// The GPU buffers only grow, so a batch reaches a steady size after a few frames.
+///
+/// The GL objects are deleted when the batch is dropped, so it has to be dropped before
+/// the window that owns the GL context.
+pub struct QuadBatch {
+    shader: ShaderProgram,
+    vao: u32,
+    vbo: u32,
+    ebo: u32,
+    capacity: usize,
+    vertices: Vec<f32>,
+    blend_mode: BlendMode,
+    draw_calls: usize,
+    vertex_count: usize,
+}
+
+impl QuadBatch {
+    pub fn new() -> Result<Self, Error> {
+        let shader = ShaderProgram::from_shaders(&[
+            Shader::from_source(
+                ShaderType::Vertex,
+                include_str!("../../../assets/shaders/batch.vert"),
+            )?,
+            Shader::from_source(
+                ShaderType::Fragment,
+                include_str!("../../../assets/shaders/batch.frag"),
+            )?,
+        ])?;
+
+        let mut vao = 0;
+        let mut vbo = 0;
+        let mut ebo = 0;
+
+        gl!(GenVertexArrays, 1, &mut vao)?;
+        GlState::bind_vertex_array(vao)?;
+        gl!(GenBuffers, 1, &mut vbo)?;
+        gl!(BindBuffer, gl::ARRAY_BUFFER, vbo)?;
+        gl!(GenBuffers, 1, &mut ebo)?;
+        gl!(BindBuffer, gl::ELEMENT_ARRAY_BUFFER, ebo)?;
+
+        let float_size = std::mem::size_of::<f32>();
+        let stride = (VERTEX_FLOATS * float_size) as i32;
+        for (location, (components, offset)) in [(2, 0), (2, 2), (4, 4)].into_iter().enumerate() {
+            gl!(
+                VertexAttribPointer,
+                location as u32,
+                components,
+                gl::FLOAT,
+                gl::FALSE,
+                stride,
+                (offset * float_size) as *const _,
+            )?;
+            gl!(EnableVertexAttribArray, location as u32)?;
+        }
+
+        GlState::bind_vertex_array(0)?;
+        gl!(BindBuffer, gl::ARRAY_BUFFER, 0)?;
+        gl!(BindBuffer, gl::ELEMENT_ARRAY_BUFFER, 0)?;
+
+        Ok(Self {
+            shader,
+            vao,
+            vbo,
+            ebo,
+            capacity: 0,
+            vertices: Vec::new(),
+            blend_mode: BlendMode::default(),
+            draw_calls: 0,
+            vertex_count: 0,
+        })
+    }
+
+    /// Add a quad, `model` transforms the unit quad into world space.
+    ///
+    /// `region` is the normalized offset (xy) and scale (zw) of the texture to sample.
+    pub fn push(&mut self, model: &Matrix4<f32>, region: (f32, f32, f32, f32), color: Color) {
+        let (u, v, width, height) = region;
+
+        for (x, y) in CORNERS {
+            let position = model.transform_point(&Point3::new(x, y, 0.0));
+            self.vertices.extend_from_slice(&[
+                position.x,
+                position.y,
+                u + x * width,
+                v + y * height,
+                color.r,
+                color.g,
+                color.b,
+                color.a,
+            ]);
+        }
+    }
+
+    /// The number of quads waiting to be flushed.
+    pub fn len(&self) -> usize {
+        self.vertices.len() / QUAD_FLOATS
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.vertices.is_empty()
+    }
+
+    pub fn set_blend_mode(&mut self, mode: BlendMode) {
+        self.blend_mode = mode;
+    }
+
+    /// Draw all of the queued quads with a texture in a single draw call and clear them.
+    pub fn flush(
+        &mut self,
+        texture: &Texture,
+        view_projection: &Matrix4<f32>,
+    ) -> Result<(), Error> {
+        let quads = self.len();
+        if quads == 0 {
+            return Ok(());
+        }
+
+        GlState::bind_vertex_array(self.vao)?;
+        gl!(BindBuffer, gl::ARRAY_BUFFER, self.vbo)?;
+
+        // Only reallocate the buffers when they need to grow.
+        if quads > self.capacity {
+            gl!(
+                BufferData,
+                gl::ARRAY_BUFFER,
+                std::mem::size_of_val(self.vertices.as_slice()) as isize,
+                self.vertices.as_ptr() as *const _,
+                gl::STREAM_DRAW,
+            )?;
+
+            let indices: Vec<u32> = (0..quads as u32)
+                .flat_map(|quad| CORNER_INDICES.map(|index| quad * 4 + index))
+                .collect();
+            gl!(
+                BufferData,
+                gl::ELEMENT_ARRAY_BUFFER,
+                std::mem::size_of_val(indices.as_slice()) as isize,
+                indices.as_ptr() as *const _,
+                gl::STATIC_DRAW,
+            )?;
+
+            self.capacity = quads;
+        } else {
+            gl!(
+                BufferSubData,
+                gl::ARRAY_BUFFER,
+                0,
+                std::mem::size_of_val(self.vertices.as_slice()) as isize,
+                self.vertices.as_ptr() as *const _,
+            )?;
+        }
+        gl!(BindBuffer, gl::ARRAY_BUFFER, 0)?;
+
+        enable_blending(self.blend_mode)?;
+
+        self.shader.use_program();
+        self.shader
+            .set_uniform_mat4("viewProjection", view_projection)?;
+
+        GlState::bind_texture(0, texture.id())?;
+
+        gl!(
+            DrawElements,
+            gl::TRIANGLES,
+            (quads * CORNER_INDICES.len()) as i32,
+            gl::UNSIGNED_INT,
+            std::ptr::null()
+        )?;
+
+        disable_blending()?;
+
+        self.draw_calls += 1;
+        self.vertex_count += quads * CORNERS.len();
+        self.vertices.clear();
+
+        Ok(())
+    }
+
+    /// The number of draw calls made since the last `reset_stats`.
+    pub fn draw_calls(&self) -> usize {
+        self.draw_calls
+    }
+
+    /// The number of vertices drawn since the last `reset_stats`.
+    pub fn vertex_count(&self) -> usize {
+        self.vertex_count
+    }
+
+    /// Reset the profiling counters, usually once per frame.
+    pub fn reset_stats(&mut self) {
+        self.draw_calls = 0;
+        self.vertex_count = 0;
+    }
+}
+
+impl Drop for QuadBatch {
+    fn drop(&mut self) {
+        // Nothing to free if GL was never loaded.
+        if !gl::DeleteVertexArrays::is_loaded() {
+            return;
+        }
+
+        let buffers = [self.vbo, self.ebo];
+        GlState::forget_vertex_array(self.vao);
+        gl_unchecked!(DeleteVertexArrays, 1, &self.vao);
+        gl_unchecked!(DeleteBuffers, 2, buffers.as_ptr());
+    }
+}
@@ -0,0 +1,25 @@
+use super::{
+    LineRenderer, Shader, ShaderProgram, ShaderType, ShapeRenderer, SpriteRenderer, Texture,
+    TileMapRenderer,
+};
+use crate::{error::Error, rendering::Renderer};
+
+/// The OpenGL 3.3 core backend.
+pub struct OpenGlRenderer;
+
+impl Renderer for OpenGlRenderer {
+    type Texture = Texture;
+    type Shader = ShaderProgram;
+    type SpriteRenderer = SpriteRenderer;
+    type ShapeRenderer = ShapeRenderer;
+    type LineRenderer = LineRenderer;
+    type TileMapRenderer = TileMapRenderer;
+
+    /// Compile and link a GLSL program.
+    fn create_shader(vertex_source: &str, fragment_source: &str) -> Result<ShaderProgram, Error> {
+        ShaderProgram::from_shaders(&[
+            Shader::from_source(ShaderType::Vertex, vertex_source)?,
+            Shader::from_source(ShaderType::Fragment, fragment_source)?,
+        ])
+    }
+}
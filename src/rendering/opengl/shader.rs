@@ -1,15 +1,23 @@
 #![allow(unused)]
 
 use std::{
+    cell::RefCell,
+    collections::HashMap,
     ffi::CString,
     path::{Path, PathBuf},
+    time::SystemTime,
 };
 
-use nalgebra::Matrix4;
+use nalgebra::{Matrix3, Matrix4, Vector2, Vector3, Vector4};
 use snafu::Snafu;
 
+use super::{
+    preprocessor::{display_name, preprocess},
+    GlState,
+};
 use crate::{
-    gl,
+    error::Error,
+    gl, gl_unchecked,
     utils::{create_whitespace_cstring_with_len, GlError},
 };
 
@@ -19,6 +27,14 @@ pub enum ShaderError {
     GlError { error: GlError },
     #[snafu(display("Uniform not found: {}", name))]
     UniformNotFound { name: String },
+    #[snafu(display("Failed to compile {}:\n{}", file, log))]
+    Compile { file: String, log: String },
+    #[snafu(display("Failed to link shader program:\n{}", log))]
+    Link { log: String },
+    #[snafu(display("Invalid #include in {}", message))]
+    Include { message: String },
+    #[snafu(display("Can't reload a shader that wasn't loaded from a file"))]
+    NotReloadable,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -63,10 +79,24 @@ pub struct Shader {
 }
 
 impl Shader {
-    pub fn from_source(kind: ShaderType, source: &str) -> Result<Self, Box<dyn std::error::Error>> {
+    /// Compile a shader from source, `#include` paths are relative to the working directory.
+    pub fn from_source(kind: ShaderType, source: &str) -> Result<Self, Error> {
+        Shader::compile(kind, source, Path::new(""))
+    }
+
+    /// Compile a shader from a file, `#include` paths are relative to the file.
+    pub fn from_file(kind: ShaderType, path: impl Into<PathBuf>) -> Result<Self, Error> {
+        let path = path.into();
+        let source = std::fs::read_to_string(&path)?;
+        Shader::compile(kind, &source, &path)
+    }
+
+    fn compile(kind: ShaderType, source: &str, location: &Path) -> Result<Self, Error> {
+        let preprocessed = preprocess(source, location)?;
+
         let id = gl!(CreateShader, kind.into())?;
 
-        let shader_source = ShaderSource::from(source);
+        let shader_source = ShaderSource::from(preprocessed.source.as_str());
         gl!(
             ShaderSource,
             id,
@@ -76,25 +106,20 @@ impl Shader {
         );
         gl!(CompileShader, id);
 
-        check_shader_error(id, gl::COMPILE_STATUS, false)?;
+        if let Some(log) = check_shader_error(id, gl::COMPILE_STATUS, false)? {
+            gl_unchecked!(DeleteShader, id);
+
+            return Err(ShaderError::Compile {
+                file: display_name(location),
+                log: preprocessed.annotate_log(&log),
+            }
+            .into());
+        }
 
         Ok(Shader {
             id,
             kind,
-            source_location: PathBuf::new(),
-        })
-    }
-
-    pub fn from_file(
-        kind: ShaderType,
-        path: impl Into<PathBuf>,
-    ) -> Result<Self, Box<dyn std::error::Error>> {
-        let path = path.into();
-        let source = std::fs::read_to_string(&path).map_err(|e| e.to_string())?;
-        let shader = Shader::from_source(kind, &source)?;
-        Ok(Shader {
-            source_location: path,
-            ..shader
+            source_location: location.to_path_buf(),
         })
     }
 
@@ -111,12 +136,29 @@ impl Shader {
     }
 }
 
+impl Drop for Shader {
+    fn drop(&mut self) {
+        if gl::DeleteShader::is_loaded() {
+            gl_unchecked!(DeleteShader, self.id);
+        }
+    }
+}
+
+/// Where a shader in a program was loaded from, used for reloading.
+struct ShaderSourceFile {
+    kind: ShaderType,
+    location: PathBuf,
+    modified: Option<SystemTime>,
+}
+
 pub struct ShaderProgram {
     id: u32,
+    sources: Vec<ShaderSourceFile>,
+    uniform_locations: RefCell<HashMap<String, i32>>,
 }
 
 impl ShaderProgram {
-    pub fn from_shaders(shaders: &[Shader]) -> Result<Self, Box<dyn std::error::Error>> {
+    pub fn from_shaders(shaders: &[Shader]) -> Result<Self, Error> {
         let id = gl!(CreateProgram)?;
 
         for shader in shaders {
@@ -125,13 +167,75 @@ impl ShaderProgram {
 
         gl!(LinkProgram, id);
 
-        check_shader_error(id, gl::LINK_STATUS, true)?;
+        if let Some(log) = check_shader_error(id, gl::LINK_STATUS, true)? {
+            gl_unchecked!(DeleteProgram, id);
+
+            return Err(ShaderError::Link { log }.into());
+        }
 
         for shader in shaders {
             gl!(DetachShader, id, shader.id())?;
         }
 
-        Ok(ShaderProgram { id })
+        let sources = shaders
+            .iter()
+            .map(|shader| ShaderSourceFile {
+                kind: shader.kind(),
+                location: shader.source_location().to_path_buf(),
+                modified: modified_time(shader.source_location()),
+            })
+            .collect();
+
+        Ok(ShaderProgram {
+            id,
+            sources,
+            uniform_locations: RefCell::new(HashMap::new()),
+        })
+    }
+
+    /// Recompile and relink the program from the files its shaders were loaded from.
+    ///
+    /// The program is only replaced if everything compiles and links, otherwise the
+    /// old program is kept and the error is returned.
+    pub fn reload(&mut self) -> Result<(), Error> {
+        let shaders = self
+            .sources
+            .iter()
+            .map(|source| {
+                if source.location.as_os_str().is_empty() {
+                    return Err(ShaderError::NotReloadable.into());
+                }
+
+                Shader::from_file(source.kind, &source.location)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // The old program is deleted when the swapped out program is dropped.
+        let mut program = ShaderProgram::from_shaders(&shaders)?;
+        std::mem::swap(self, &mut program);
+
+        Ok(())
+    }
+
+    /// Reload the program if any of its shader files changed since they were last loaded,
+    /// returning whether a reload happened. Call this once per frame to watch the files.
+    pub fn reload_if_changed(&mut self) -> Result<bool, Error> {
+        let mut changed = false;
+
+        for source in &mut self.sources {
+            let modified = modified_time(&source.location);
+            if modified != source.modified {
+                // Remember the time even if reloading fails, to retry on the next change.
+                source.modified = modified;
+                changed = true;
+            }
+        }
+
+        if changed {
+            self.reload()?;
+        }
+
+        Ok(changed)
     }
 
     pub fn id(&self) -> u32 {
@@ -139,7 +243,7 @@ impl ShaderProgram {
     }
 
     pub fn use_program(&self) {
-        gl!(UseProgram, self.id);
+        GlState::use_program(self.id);
     }
 
     pub fn set_uniform_1f(&self, name: &str, value: f32) -> Result<(), ShaderError> {
@@ -152,6 +256,16 @@ impl ShaderProgram {
             .map_err(|e| ShaderError::GlError { error: e })
     }
 
+    pub fn set_uniform_2f(&self, name: &str, value: (f32, f32)) -> Result<(), ShaderError> {
+        gl!(
+            Uniform2f,
+            self.get_uniform_location(name)?,
+            value.0,
+            value.1
+        )
+        .map_err(|e| ShaderError::GlError { error: e })
+    }
+
     pub fn set_uniform_3f(&self, name: &str, value: (f32, f32, f32)) -> Result<(), ShaderError> {
         gl!(
             Uniform3f,
@@ -179,6 +293,50 @@ impl ShaderProgram {
         .map_err(|e| ShaderError::GlError { error: e })
     }
 
+    pub fn set_uniform_vec2(&self, name: &str, value: &Vector2<f32>) -> Result<(), ShaderError> {
+        gl!(
+            Uniform2f,
+            self.get_uniform_location(name)?,
+            value.x,
+            value.y
+        )
+        .map_err(|e| ShaderError::GlError { error: e })
+    }
+
+    pub fn set_uniform_vec3(&self, name: &str, value: &Vector3<f32>) -> Result<(), ShaderError> {
+        gl!(
+            Uniform3f,
+            self.get_uniform_location(name)?,
+            value.x,
+            value.y,
+            value.z
+        )
+        .map_err(|e| ShaderError::GlError { error: e })
+    }
+
+    pub fn set_uniform_vec4(&self, name: &str, value: &Vector4<f32>) -> Result<(), ShaderError> {
+        gl!(
+            Uniform4f,
+            self.get_uniform_location(name)?,
+            value.x,
+            value.y,
+            value.z,
+            value.w
+        )
+        .map_err(|e| ShaderError::GlError { error: e })
+    }
+
+    pub fn set_uniform_mat3(&self, name: &str, value: &Matrix3<f32>) -> Result<(), ShaderError> {
+        gl!(
+            UniformMatrix3fv,
+            self.get_uniform_location(name)?,
+            1,
+            gl::FALSE,
+            value.as_ptr(),
+        )
+        .map_err(|e| ShaderError::GlError { error: e })
+    }
+
     pub fn set_uniform_mat4(&self, name: &str, value: &Matrix4<f32>) -> Result<(), ShaderError> {
         gl!(
             UniformMatrix4fv,
@@ -191,8 +349,19 @@ impl ShaderProgram {
     }
 
     fn get_uniform_location(&self, name: &str) -> Result<i32, ShaderError> {
-        let name_cstr = CString::new(name).unwrap();
-        let location = unsafe { gl::GetUniformLocation(self.id, name_cstr.as_ptr()) };
+        // Only ask the driver the first time, missing uniforms are cached as -1.
+        let cached = self.uniform_locations.borrow().get(name).copied();
+        let location = match cached {
+            Some(location) => location,
+            None => {
+                let name_cstr = CString::new(name).unwrap();
+                let location = unsafe { gl::GetUniformLocation(self.id, name_cstr.as_ptr()) };
+                self.uniform_locations
+                    .borrow_mut()
+                    .insert(name.to_string(), location);
+                location
+            }
+        };
 
         if location == -1 {
             return Err(ShaderError::UniformNotFound {
@@ -204,11 +373,24 @@ impl ShaderProgram {
     }
 }
 
-fn check_shader_error(
-    shader: u32,
-    flag: u32,
-    is_program: bool,
-) -> Result<(), Box<dyn std::error::Error>> {
+impl Drop for ShaderProgram {
+    fn drop(&mut self) {
+        if gl::DeleteProgram::is_loaded() {
+            GlState::forget_program(self.id);
+            gl_unchecked!(DeleteProgram, self.id);
+        }
+    }
+}
+
+/// Get the last modified time of a file, if it exists.
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// Check the compile or link status, returning the info log if it failed.
+fn check_shader_error(shader: u32, flag: u32, is_program: bool) -> Result<Option<String>, GlError> {
     let mut success = 0;
     let mut len = 0;
 
@@ -245,8 +427,8 @@ fn check_shader_error(
             )?;
         }
 
-        return Err(error.to_string_lossy().into_owned().into());
+        return Ok(Some(error.to_string_lossy().into_owned()));
     }
 
-    Ok(())
+    Ok(None)
 }
@@ -0,0 +1,190 @@
+use std::cell::RefCell;
+
+use nalgebra::{Matrix4, Vector2};
+
+use super::colored_buffer::{push_vertex, ColoredBuffer};
+use crate::{
+    error::Error,
+    rendering::{Color, Rect, ShapeRendererTrait},
+};
+
+// The number of triangles used to approximate a circle.
+const CIRCLE_SEGMENTS: usize = 48;
+
+/// Renders flat colored shapes, each shape is drawn with a single draw call.
+///
+/// The GL objects are deleted when the renderer is dropped, so it has to be dropped
+/// before the window that owns the GL context.
+pub struct ShapeRenderer {
+    buffer: ColoredBuffer,
+    vertices: RefCell<Vec<f32>>,
+}
+
+impl ShapeRenderer {
+    /// Draw the triangles built by `build` into the vertex list.
+    fn draw_triangles(
+        &self,
+        projection: &Matrix4<f32>,
+        view: &Matrix4<f32>,
+        build: impl FnOnce(&mut Vec<f32>),
+    ) -> Result<(), Error> {
+        let mut vertices = self.vertices.borrow_mut();
+        vertices.clear();
+        build(&mut vertices);
+
+        self.buffer
+            .draw(gl::TRIANGLES, &(projection * view), &vertices)
+    }
+}
+
+/// Append two triangles covering the quad with corners `a`, `b`, `c`, `d` in order.
+fn push_quad(vertices: &mut Vec<f32>, corners: [Vector2<f32>; 4], color: Color) {
+    let [a, b, c, d] = corners;
+    for corner in [a, b, c, a, c, d] {
+        push_vertex(vertices, corner, color);
+    }
+}
+
+/// Append a quad `thickness` wide centered on the line from `start` to `end`.
+fn push_line(
+    vertices: &mut Vec<f32>,
+    start: Vector2<f32>,
+    end: Vector2<f32>,
+    thickness: f32,
+    color: Color,
+) {
+    let direction = end - start;
+    if direction.norm_squared() == 0.0 {
+        return;
+    }
+
+    let normal = Vector2::new(-direction.y, direction.x).normalize() * (thickness / 2.0);
+    push_quad(
+        vertices,
+        [start + normal, end + normal, end - normal, start - normal],
+        color,
+    );
+}
+
+fn rect_corners(rect: &Rect) -> [Vector2<f32>; 4] {
+    [
+        Vector2::new(rect.x, rect.y),
+        Vector2::new(rect.x + rect.width, rect.y),
+        Vector2::new(rect.x + rect.width, rect.y + rect.height),
+        Vector2::new(rect.x, rect.y + rect.height),
+    ]
+}
+
+impl ShapeRendererTrait for ShapeRenderer {
+    fn new() -> Result<Self, Error> {
+        Ok(Self {
+            buffer: ColoredBuffer::new()?,
+            vertices: RefCell::new(Vec::new()),
+        })
+    }
+
+    fn fill_rect(
+        &self,
+        projection: &Matrix4<f32>,
+        view: &Matrix4<f32>,
+        rect: &Rect,
+        color: impl Into<Color>,
+    ) -> Result<(), Error> {
+        let color = color.into();
+
+        self.draw_triangles(projection, view, |vertices| {
+            push_quad(vertices, rect_corners(rect), color)
+        })
+    }
+
+    fn stroke_rect(
+        &self,
+        projection: &Matrix4<f32>,
+        view: &Matrix4<f32>,
+        rect: &Rect,
+        thickness: f32,
+        color: impl Into<Color>,
+    ) -> Result<(), Error> {
+        let color = color.into();
+        let thickness = thickness.min(rect.width / 2.0).min(rect.height / 2.0);
+        let inner = Rect::new(
+            rect.x + thickness,
+            rect.y + thickness,
+            rect.width - thickness * 2.0,
+            rect.height - thickness * 2.0,
+        );
+
+        self.draw_triangles(projection, view, |vertices| {
+            let outer = rect_corners(rect);
+            let inner = rect_corners(&inner);
+
+            // One quad per side, between the outer and inner corners.
+            for side in 0..4 {
+                let next = (side + 1) % 4;
+                push_quad(
+                    vertices,
+                    [outer[side], outer[next], inner[next], inner[side]],
+                    color,
+                );
+            }
+        })
+    }
+
+    fn line(
+        &self,
+        projection: &Matrix4<f32>,
+        view: &Matrix4<f32>,
+        start: Vector2<f32>,
+        end: Vector2<f32>,
+        thickness: f32,
+        color: impl Into<Color>,
+    ) -> Result<(), Error> {
+        let color = color.into();
+
+        self.draw_triangles(projection, view, |vertices| {
+            push_line(vertices, start, end, thickness, color)
+        })
+    }
+
+    fn circle(
+        &self,
+        projection: &Matrix4<f32>,
+        view: &Matrix4<f32>,
+        center: Vector2<f32>,
+        radius: f32,
+        color: impl Into<Color>,
+    ) -> Result<(), Error> {
+        let color = color.into();
+        let point = |segment: usize| {
+            let angle = segment as f32 / CIRCLE_SEGMENTS as f32 * std::f32::consts::TAU;
+            center + Vector2::new(angle.cos(), angle.sin()) * radius
+        };
+
+        self.draw_triangles(projection, view, |vertices| {
+            for segment in 0..CIRCLE_SEGMENTS {
+                push_vertex(vertices, center, color);
+                push_vertex(vertices, point(segment), color);
+                push_vertex(vertices, point(segment + 1), color);
+            }
+        })
+    }
+
+    fn polygon(
+        &self,
+        projection: &Matrix4<f32>,
+        view: &Matrix4<f32>,
+        points: &[Vector2<f32>],
+        color: impl Into<Color>,
+    ) -> Result<(), Error> {
+        let color = color.into();
+
+        // Fan out from the first point, which only works for convex polygons.
+        self.draw_triangles(projection, view, |vertices| {
+            for edge in points.windows(2).skip(1) {
+                push_vertex(vertices, points[0], color);
+                push_vertex(vertices, edge[0], color);
+                push_vertex(vertices, edge[1], color);
+            }
+        })
+    }
+}
@@ -1,88 +1,430 @@
-use nalgebra::{Matrix4, Vector2, Vector3};
+use std::{
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
 
-use super::{Shader, ShaderProgram, ShaderType, Texture};
-use crate::{gl, rendering::SpriteRendererTrait};
+use nalgebra::{Matrix4, Vector2, Vector3, Vector4};
 
-static QUAD_VERTICES: [f32; 24] = [
-    0.0, 1.0, 0.0, 1.0, 1.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 1.0, 1.0, 1.0, 1.0,
-    1.0, 1.0, 0.0, 1.0, 0.0,
+use super::{
+    blend_mode::{disable_blending, enable_blending},
+    GlState, Mesh, Shader, ShaderError, ShaderProgram, ShaderType, Texture,
+};
+use crate::{
+    error::Error,
+    gl, gl_unchecked,
+    rendering::{
+        BlendMode, Color, DrawParams, FlushStats, Rect, SortMode, SpriteInstance,
+        SpriteRendererTrait,
+    },
+};
+
+// A unit quad of positions (xy) and texture coordinates (zw).
+static QUAD_VERTICES: [f32; 16] = [
+    0.0, 1.0, 0.0, 1.0, //
+    1.0, 0.0, 1.0, 0.0, //
+    0.0, 0.0, 0.0, 0.0, //
+    1.0, 1.0, 1.0, 1.0, //
 ];
+static QUAD_INDICES: [u32; 6] = [0, 1, 2, 0, 3, 1];
+static QUAD_LAYOUT: [i32; 2] = [2, 2];
+
+// Sample the whole texture.
+const FULL_REGION: (f32, f32, f32, f32) = (0.0, 0.0, 1.0, 1.0);
+
+// Per instance data, a mat4 model matrix followed by a vec4 color.
+const INSTANCE_FLOATS: usize = 16 + 4;
+
+/// A sprite waiting in the queue to be sorted and drawn.
+struct QueuedSprite {
+    texture: Rc<Texture>,
+    transform: Matrix4<f32>,
+    color: Color,
+    depth: f32,
+}
+
+/// Build the model matrix for a unit quad scaled to `size`, rotated by `rotation` radians
+/// around `origin` (normalized to the size) and moved to `position`.
+fn model_matrix(
+    position: &Vector3<f32>,
+    size: &Vector2<f32>,
+    rotation: f32,
+    origin: &Vector2<f32>,
+) -> Matrix4<f32> {
+    let pivot = Vector3::new(origin.x * size.x, origin.y * size.y, 0.0);
 
+    Matrix4::new_translation(position)
+        * Matrix4::new_translation(&pivot)
+        * Matrix4::new_rotation(Vector3::z() * rotation)
+        * Matrix4::new_translation(&-pivot)
+        * Matrix4::new_nonuniform_scaling(&Vector3::new(size.x, size.y, 1.0))
+}
+
+/// Renders textured quads.
+///
+/// The GL objects are deleted when the renderer is dropped, so it has to be dropped
+/// before the window that owns the GL context.
 pub struct SpriteRenderer {
     shader: ShaderProgram,
-    quad_vao: u32,
-    _quad_vbo: u32,
+    quad: Mesh,
+    instanced_shader: ShaderProgram,
+    instance_vao: u32,
+    instance_vbo: u32,
+    instance_capacity: Cell<usize>,
+    instance_data: RefCell<Vec<f32>>,
+    blend_mode: BlendMode,
+    queue: RefCell<Vec<QueuedSprite>>,
+    sort_mode: SortMode,
+    flush_stats: Cell<FlushStats>,
+}
+
+impl SpriteRenderer {
+    fn setup_instance_attributes() -> Result<(), Error> {
+        let stride = (INSTANCE_FLOATS * std::mem::size_of::<f32>()) as i32;
+
+        // The mat4 model takes up 4 consecutive vec4 locations, followed by the vec4 color.
+        for column in 0..5 {
+            let location = 2 + column;
+            gl!(
+                VertexAttribPointer,
+                location,
+                4,
+                gl::FLOAT,
+                gl::FALSE,
+                stride,
+                (column as usize * 4 * std::mem::size_of::<f32>()) as *const _,
+            )?;
+            gl!(EnableVertexAttribArray, location)?;
+            gl!(VertexAttribDivisor, location, 1)?;
+        }
+
+        Ok(())
+    }
+
+    /// Convert a region of a texture in pixels to a normalized offset and scale.
+    fn normalized_region(texture: &Texture, source: &Rect) -> (f32, f32, f32, f32) {
+        let width = texture.width() as f32;
+        let height = texture.height() as f32;
+
+        (
+            source.x / width,
+            source.y / height,
+            source.width / width,
+            source.height / height,
+        )
+    }
+
+    /// Draw a sprite using a custom shader program instead of the built-in one.
+    ///
+    /// The shader has to follow the same contract as the built-in sprite shader:
+    /// - `layout (location = 0) in vec2 vertexPosition`, the unit quad position.
+    /// - `layout (location = 1) in vec2 textureCoordinate`, the unit quad texture coordinate.
+    /// - `uniform mat4 transform`, the combined projection, view and model matrix.
+    /// - `uniform vec4 spriteColor`, the color and alpha of the sprite.
+    /// - `uniform sampler2D`, the texture is bound to texture unit 0.
+    /// - `uniform vec4 textureRegion` (optional), the normalized offset (xy) and scale (zw)
+    ///   of the texture region to sample.
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_with(
+        &self,
+        shader: &ShaderProgram,
+        texture: &Texture,
+        projection: &Matrix4<f32>,
+        view: &Matrix4<f32>,
+        position: &Vector3<f32>,
+        size: &Vector2<f32>,
+        rotation: f32,
+        origin: &Vector2<f32>,
+        color: impl Into<Color>,
+    ) -> Result<(), Error> {
+        let model = model_matrix(position, size, rotation, origin);
+        self.draw_quad_with(
+            shader,
+            texture.id(),
+            &(projection * view * model),
+            color.into(),
+            FULL_REGION,
+        )
+    }
+
+    /// Draw a single textured quad with a complete transform.
+    fn draw_quad(
+        &self,
+        texture: u32,
+        transform: &Matrix4<f32>,
+        color: Color,
+        region: (f32, f32, f32, f32),
+    ) -> Result<(), Error> {
+        self.draw_quad_with(&self.shader, texture, transform, color, region)
+    }
+
+    /// Draw a single textured quad with a complete transform and shader program.
+    fn draw_quad_with(
+        &self,
+        shader: &ShaderProgram,
+        texture: u32,
+        transform: &Matrix4<f32>,
+        color: Color,
+        region: (f32, f32, f32, f32),
+    ) -> Result<(), Error> {
+        // Enable blending.
+        enable_blending(self.blend_mode)?;
+
+        // Use the shader program
+        shader.use_program();
+
+        // Bind the texture
+        GlState::bind_texture(0, texture)?;
+
+        // Set the uniforms
+        shader.set_uniform_mat4("transform", transform)?;
+        shader.set_uniform_4f("spriteColor", color.into())?;
+        match shader.set_uniform_4f("textureRegion", region) {
+            // Custom shaders don't have to support texture regions.
+            Err(ShaderError::UniformNotFound { .. }) => {}
+            result => result?,
+        }
+
+        // Draw the quad
+        self.quad.draw()?;
+
+        // Disable blending.
+        disable_blending()?;
+
+        Ok(())
+    }
+
+    /// Draw a sprite described by `params`, `transform` is everything applied after the
+    /// sprite's own model matrix.
+    fn draw_params_at(
+        &self,
+        texture: &Texture,
+        transform: &Matrix4<f32>,
+        params: &DrawParams,
+    ) -> Result<(), Error> {
+        let size = params
+            .size
+            .unwrap_or_else(|| Vector2::new(texture.width() as f32, texture.height() as f32));
+        let model = model_matrix(&params.position, &size, params.rotation, &params.origin);
+
+        let (mut offset_x, mut offset_y, mut scale_x, mut scale_y) = match &params.source {
+            Some(source) => Self::normalized_region(texture, source),
+            None => FULL_REGION,
+        };
+
+        // Flip by sampling the region backwards.
+        if params.flip.0 {
+            offset_x += scale_x;
+            scale_x = -scale_x;
+        }
+        if params.flip.1 {
+            offset_y += scale_y;
+            scale_y = -scale_y;
+        }
+
+        self.draw_quad(
+            texture.id(),
+            &(transform * model),
+            params.color,
+            (offset_x, offset_y, scale_x, scale_y),
+        )
+    }
+
+    /// Draw many quads with pre-computed model matrices in a single instanced draw call.
+    ///
+    /// Each transform is the model matrix of a unit quad, as the sprites in `draw` are built.
+    pub fn draw_instanced(
+        &self,
+        texture: &Texture,
+        projection: &Matrix4<f32>,
+        view: &Matrix4<f32>,
+        transforms: &[Matrix4<f32>],
+        colors: &[Color],
+    ) -> Result<(), Error> {
+        if transforms.len() != colors.len() {
+            return Err(Error::InvalidArgument {
+                message: format!(
+                    "mismatched instance data, {} transforms and {} colors",
+                    transforms.len(),
+                    colors.len()
+                ),
+            });
+        }
+
+        let mut instance_data = self.instance_data.borrow_mut();
+        instance_data.clear();
+        for (transform, color) in transforms.iter().zip(colors) {
+            instance_data.extend_from_slice(transform.as_slice());
+            instance_data.extend_from_slice(&[color.r, color.g, color.b, color.a]);
+        }
+
+        self.draw_instances(texture, &(projection * view), &instance_data)
+    }
+
+    /// Upload the per instance data and draw all instances in a single draw call.
+    fn draw_instances(
+        &self,
+        texture: &Texture,
+        view_projection: &Matrix4<f32>,
+        instance_data: &[f32],
+    ) -> Result<(), Error> {
+        let count = instance_data.len() / INSTANCE_FLOATS;
+        if count == 0 {
+            return Ok(());
+        }
+
+        // Enable blending.
+        enable_blending(self.blend_mode)?;
+
+        self.instanced_shader.use_program();
+        self.instanced_shader
+            .set_uniform_mat4("viewProjection", view_projection)?;
+
+        // Only reallocate the instance buffer when it needs to grow.
+        gl!(BindBuffer, gl::ARRAY_BUFFER, self.instance_vbo)?;
+        if count > self.instance_capacity.get() {
+            gl!(
+                BufferData,
+                gl::ARRAY_BUFFER,
+                std::mem::size_of_val(instance_data) as isize,
+                instance_data.as_ptr() as *const _,
+                gl::STREAM_DRAW,
+            )?;
+            self.instance_capacity.set(count);
+        } else {
+            gl!(
+                BufferSubData,
+                gl::ARRAY_BUFFER,
+                0,
+                std::mem::size_of_val(instance_data) as isize,
+                instance_data.as_ptr() as *const _,
+            )?;
+        }
+        gl!(BindBuffer, gl::ARRAY_BUFFER, 0)?;
+
+        // Bind the texture and vertex array
+        GlState::bind_vertex_array(self.instance_vao)?;
+        GlState::bind_texture(0, texture.id())?;
+
+        // Draw all the quads
+        self.quad.draw_bound(count as i32)?;
+
+        // Disable blending.
+        disable_blending()?;
+
+        Ok(())
+    }
 }
 
 impl SpriteRendererTrait for SpriteRenderer {
     type Texture = Texture;
 
-    fn new() -> Result<Self, Box<dyn std::error::Error>> {
+    fn new() -> Result<Self, Error> {
         // Create the shader program for the sprite renderer
         let shader = ShaderProgram::from_shaders(&[
             Shader::from_source(
                 ShaderType::Vertex,
                 include_str!("../../../assets/shaders/sprite.vert"),
-            )
-            .unwrap(),
+            )?,
             Shader::from_source(
                 ShaderType::Fragment,
                 include_str!("../../../assets/shaders/sprite.frag"),
-            )
-            .unwrap(),
-        ])
-        .unwrap();
-
-        // Setup the quad VAO and VBO
-        let mut quad_vao = 0;
-        let mut quad_vbo = 0;
-
-        // Bind the VAO
-        gl!(GenVertexArrays, 1, &mut quad_vao)?;
-        gl!(BindVertexArray, quad_vao)?;
-
-        // Bind the VBO
-        gl!(GenBuffers, 1, &mut quad_vbo)?;
-        gl!(BindBuffer, gl::ARRAY_BUFFER, quad_vbo)?;
-
-        // Buffer the quad data.
-        gl!(
-            BufferData,
-            gl::ARRAY_BUFFER,
-            (QUAD_VERTICES.len() * std::mem::size_of::<f32>()) as isize,
-            QUAD_VERTICES.as_ptr() as *const _,
-            gl::STATIC_DRAW,
-        )?;
-
-        // Setup the vertex attributes
-        gl!(
-            VertexAttribPointer,
-            0,
-            2,
-            gl::FLOAT,
-            gl::FALSE,
-            4 * std::mem::size_of::<f32>() as i32,
-            std::ptr::null(),
-        )?;
-        gl!(EnableVertexAttribArray, 0)?;
-        gl!(
-            VertexAttribPointer,
-            1,
-            2,
-            gl::FLOAT,
-            gl::FALSE,
-            4 * std::mem::size_of::<f32>() as i32,
-            (2 * std::mem::size_of::<f32>()) as *const _,
-        )?;
-        gl!(EnableVertexAttribArray, 1)?;
+            )?,
+        ])?;
+
+        // Create the shader program for instanced sprites
+        let instanced_shader = ShaderProgram::from_shaders(&[
+            Shader::from_source(
+                ShaderType::Vertex,
+                include_str!("../../../assets/shaders/sprite_instanced.vert"),
+            )?,
+            Shader::from_source(
+                ShaderType::Fragment,
+                include_str!("../../../assets/shaders/sprite_instanced.frag"),
+            )?,
+        ])?;
+
+        let quad = Mesh::new(&QUAD_VERTICES, &QUAD_LAYOUT, Some(&QUAD_INDICES))?;
+
+        // Setup the instanced VAO, sharing the quad buffers
+        let mut instance_vao = 0;
+        let mut instance_vbo = 0;
+
+        gl!(GenVertexArrays, 1, &mut instance_vao)?;
+        GlState::bind_vertex_array(instance_vao)?;
+        quad.setup_vertex_array()?;
+
+        gl!(GenBuffers, 1, &mut instance_vbo)?;
+        gl!(BindBuffer, gl::ARRAY_BUFFER, instance_vbo)?;
+        Self::setup_instance_attributes()?;
+
+        GlState::bind_vertex_array(0)?;
+        gl!(BindBuffer, gl::ARRAY_BUFFER, 0)?;
 
         Ok(Self {
             shader,
-            quad_vao,
-            _quad_vbo: quad_vbo,
+            quad,
+            instanced_shader,
+            instance_vao,
+            instance_vbo,
+            instance_capacity: Cell::new(0),
+            instance_data: RefCell::new(Vec::new()),
+            blend_mode: BlendMode::default(),
+            queue: RefCell::new(Vec::new()),
+            sort_mode: SortMode::default(),
+            flush_stats: Cell::new(FlushStats::default()),
         })
     }
 
+    fn set_blend_mode(&mut self, mode: BlendMode) {
+        self.blend_mode = mode;
+    }
+
+    fn blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+
+    fn set_sort_mode(&mut self, mode: SortMode) {
+        self.sort_mode = mode;
+    }
+
+    fn sort_mode(&self) -> SortMode {
+        self.sort_mode
+    }
+
+    fn flush_stats(&self) -> FlushStats {
+        self.flush_stats.get()
+    }
+
+    fn draw_params(
+        &self,
+        texture: &Texture,
+        projection: &Matrix4<f32>,
+        view: &Matrix4<f32>,
+        params: &DrawParams,
+    ) -> Result<(), Error> {
+        self.draw_combined(texture, &(projection * view), params)
+    }
+
+    fn draw_combined(
+        &self,
+        texture: &Texture,
+        view_projection: &Matrix4<f32>,
+        params: &DrawParams,
+    ) -> Result<(), Error> {
+        self.draw_params_at(texture, view_projection, params)
+    }
+
+    fn draw_transformed(
+        &self,
+        texture: &Texture,
+        projection: &Matrix4<f32>,
+        view: &Matrix4<f32>,
+        transform: &Matrix4<f32>,
+        params: &DrawParams,
+    ) -> Result<(), Error> {
+        self.draw_params_at(texture, &(projection * view * transform), params)
+    }
+
     fn draw(
         &self,
         texture: &Texture,
@@ -91,41 +433,284 @@ impl SpriteRendererTrait for SpriteRenderer {
         position: &Vector3<f32>,
         size: &Vector2<f32>,
         rotation: f32,
-        color: &Vector3<f32>,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        // Enable blending.
-        gl!(Enable, gl::BLEND)?;
-        gl!(BlendFunc, gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA)?;
+        origin: &Vector2<f32>,
+        color: impl Into<Color>,
+    ) -> Result<(), Error> {
+        let params = DrawParams::new()
+            .position(*position)
+            .size(*size)
+            .rotation(rotation)
+            .origin(*origin)
+            .color(color);
 
-        // Use the shader program
-        self.shader.use_program();
+        self.draw_params(texture, projection, view, &params)
+    }
 
-        // Calculate the transform matrix.
-        let model = Matrix4::new_translation(position)
-            * Matrix4::new_translation(&Vector3::new(0.5 * size.x, 0.5 * size.y, 0.0))
-            * Matrix4::new_rotation(&Vector3::z() * rotation)
-            * Matrix4::new_translation(&Vector3::new(-0.5 * size.x, -0.5 * size.y, 0.0))
-            * Matrix4::new_nonuniform_scaling(&Vector3::new(size.x, size.y, 1.0));
+    fn draw_region(
+        &self,
+        texture: &Texture,
+        projection: &Matrix4<f32>,
+        view: &Matrix4<f32>,
+        position: &Vector3<f32>,
+        size: &Vector2<f32>,
+        rotation: f32,
+        origin: &Vector2<f32>,
+        color: impl Into<Color>,
+        source: &Rect,
+    ) -> Result<(), Error> {
+        let params = DrawParams::new()
+            .position(*position)
+            .size(*size)
+            .rotation(rotation)
+            .origin(*origin)
+            .color(color)
+            .source(*source);
 
-        let transform = projection * view * model;
+        self.draw_params(texture, projection, view, &params)
+    }
 
-        // Bind the texture and vertex array
-        gl!(BindVertexArray, self.quad_vao)?;
-        gl!(ActiveTexture, gl::TEXTURE0)?;
-        gl!(BindTexture, gl::TEXTURE_2D, texture.id())?;
+    fn draw_nine_slice(
+        &self,
+        texture: &Texture,
+        projection: &Matrix4<f32>,
+        view: &Matrix4<f32>,
+        destination: &Rect,
+        border: &Vector4<f32>,
+        color: impl Into<Color>,
+    ) -> Result<(), Error> {
+        let color = color.into();
+        let (width, height) = (texture.width() as f32, texture.height() as f32);
+        let (left, right, top, bottom) = (border.x, border.y, border.z, border.w);
 
-        // Set the uniforms
-        self.shader.set_uniform_mat4("transform", &transform)?;
-        self.shader
-            .set_uniform_3f("spriteColor", (color.x, color.y, color.z))?;
+        // Shrink the borders if the destination is too small to fit them.
+        let scale_x = (destination.width / (left + right)).min(1.0);
+        let scale_y = (destination.height / (top + bottom)).min(1.0);
 
-        // Draw the quad
-        gl!(DrawArrays, gl::TRIANGLES, 0, 6)?;
-        gl!(BindVertexArray, 0)?;
+        let source_xs = [0.0, left, width - right, width];
+        let source_ys = [0.0, top, height - bottom, height];
+        let destination_xs = [
+            destination.x,
+            destination.x + left * scale_x,
+            destination.x + destination.width - right * scale_x,
+            destination.x + destination.width,
+        ];
+        let destination_ys = [
+            destination.y,
+            destination.y + top * scale_y,
+            destination.y + destination.height - bottom * scale_y,
+            destination.y + destination.height,
+        ];
 
-        // Disable blending.
-        gl!(Disable, gl::BLEND)?;
+        for row in 0..3 {
+            for column in 0..3 {
+                let size = Vector2::new(
+                    destination_xs[column + 1] - destination_xs[column],
+                    destination_ys[row + 1] - destination_ys[row],
+                );
+
+                // Skip any slices with no area, like borders of zero width.
+                if size.x <= 0.0 || size.y <= 0.0 {
+                    continue;
+                }
+
+                let source = Rect::new(
+                    source_xs[column],
+                    source_ys[row],
+                    source_xs[column + 1] - source_xs[column],
+                    source_ys[row + 1] - source_ys[row],
+                );
+
+                let params = DrawParams::new()
+                    .position(Vector3::new(
+                        destination_xs[column],
+                        destination_ys[row],
+                        0.0,
+                    ))
+                    .size(size)
+                    .color(color)
+                    .source(source);
+
+                self.draw_params(texture, projection, view, &params)?;
+            }
+        }
 
         Ok(())
     }
+
+    fn draw_tiled(
+        &self,
+        texture: &Texture,
+        projection: &Matrix4<f32>,
+        view: &Matrix4<f32>,
+        position: &Vector3<f32>,
+        size: &Vector2<f32>,
+        tile_size: &Vector2<f32>,
+    ) -> Result<(), Error> {
+        if tile_size.x <= 0.0 || tile_size.y <= 0.0 {
+            return Err(Error::InvalidArgument {
+                message: format!(
+                    "tile size must be positive, got {}x{}",
+                    tile_size.x, tile_size.y
+                ),
+            });
+        }
+
+        // Sample past the edges of the texture, letting the wrap mode repeat it.
+        let tiles = size.component_div(tile_size);
+        let (repeats_x, repeats_y) = texture.repeats();
+        if (tiles.x.abs() > 1.0 && !repeats_x) || (tiles.y.abs() > 1.0 && !repeats_y) {
+            return Err(Error::InvalidArgument {
+                message: "tiled textures need a repeating wrap mode".to_string(),
+            });
+        }
+        let source = Rect::new(
+            0.0,
+            0.0,
+            texture.width() as f32 * tiles.x,
+            texture.height() as f32 * tiles.y,
+        );
+
+        let params = DrawParams::new()
+            .position(*position)
+            .size(*size)
+            .source(source);
+
+        self.draw_params(texture, projection, view, &params)
+    }
+
+    fn queue(
+        &mut self,
+        texture: &Rc<Texture>,
+        projection: &Matrix4<f32>,
+        view: &Matrix4<f32>,
+        position: &Vector3<f32>,
+        size: &Vector2<f32>,
+        rotation: f32,
+        origin: &Vector2<f32>,
+        color: impl Into<Color>,
+    ) {
+        let model = model_matrix(position, size, rotation, origin);
+
+        self.queue.get_mut().push(QueuedSprite {
+            texture: Rc::clone(texture),
+            transform: projection * view * model,
+            color: color.into(),
+            depth: position.z,
+        });
+    }
+
+    fn flush(&self) -> Result<(), Error> {
+        let mut queue = self.queue.borrow_mut();
+
+        // Stable sorts, keeping the submission order for sprites that compare equal.
+        match self.sort_mode {
+            SortMode::Depth => queue.sort_by(|a, b| a.depth.total_cmp(&b.depth)),
+            SortMode::DepthThenTexture => queue.sort_by(|a, b| {
+                a.depth
+                    .total_cmp(&b.depth)
+                    .then(a.texture.id().cmp(&b.texture.id()))
+            }),
+            SortMode::Texture => queue.sort_by(|a, b| {
+                a.texture
+                    .id()
+                    .cmp(&b.texture.id())
+                    .then(a.depth.total_cmp(&b.depth))
+            }),
+        }
+
+        let mut stats = FlushStats::default();
+        let mut instance_data = self.instance_data.borrow_mut();
+        let mut result = Ok(());
+
+        // Each run of sprites sharing a texture is drawn with a single instanced call, the
+        // transforms already include the projection and view.
+        for run in queue.chunk_by(|a, b| a.texture.id() == b.texture.id()) {
+            instance_data.clear();
+            for sprite in run {
+                instance_data.extend_from_slice(sprite.transform.as_slice());
+                let color = sprite.color;
+                instance_data.extend_from_slice(&[color.r, color.g, color.b, color.a]);
+            }
+
+            result = self.draw_instances(&run[0].texture, &Matrix4::identity(), &instance_data);
+            if result.is_err() {
+                break;
+            }
+            stats.texture_binds += 1;
+            stats.draw_calls += 1;
+        }
+
+        // Drop the queue even if drawing failed, releasing the textures it holds on to.
+        queue.clear();
+        self.flush_stats.set(stats);
+
+        result
+    }
+
+    fn draw_batch(
+        &self,
+        texture: &Texture,
+        projection: &Matrix4<f32>,
+        view: &Matrix4<f32>,
+        instances: &[SpriteInstance],
+    ) -> Result<(), Error> {
+        let mut instance_data = self.instance_data.borrow_mut();
+        instance_data.clear();
+        for instance in instances {
+            let model = model_matrix(
+                &instance.position,
+                &instance.size,
+                instance.rotation,
+                &instance.origin,
+            );
+            instance_data.extend_from_slice(model.as_slice());
+            let color = instance.color;
+            instance_data.extend_from_slice(&[color.r, color.g, color.b, color.a]);
+        }
+
+        self.draw_instances(texture, &(projection * view), &instance_data)
+    }
+}
+
+impl Drop for SpriteRenderer {
+    fn drop(&mut self) {
+        // Nothing to free if GL was never loaded.
+        if !gl::DeleteVertexArrays::is_loaded() {
+            return;
+        }
+
+        GlState::forget_vertex_array(self.instance_vao);
+        gl_unchecked!(DeleteVertexArrays, 1, &self.instance_vao);
+        gl_unchecked!(DeleteBuffers, 1, &self.instance_vbo);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::Point3;
+    use std::f32::consts::FRAC_PI_2;
+
+    const EPSILON: f32 = 1e-4;
+
+    #[test]
+    fn model_matrix_rotates_around_the_origin() {
+        let width = 32.0;
+        let model = model_matrix(
+            &Vector3::zeros(),
+            &Vector2::new(width, 16.0),
+            FRAC_PI_2,
+            &Vector2::zeros(),
+        );
+
+        // The top-left pivot stays put while the top-right corner swings down.
+        let pivot = model.transform_point(&Point3::new(0.0, 0.0, 0.0));
+        let corner = model.transform_point(&Point3::new(1.0, 0.0, 0.0));
+
+        assert!((pivot - Point3::origin()).norm() < EPSILON, "{pivot:?}");
+        assert!(
+            (corner - Point3::new(0.0, width, 0.0)).norm() < EPSILON,
+            "{corner:?}"
+        );
+    }
 }
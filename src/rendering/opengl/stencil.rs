@@ -0,0 +1,70 @@
+use crate::{error::Error, gl, gl_unchecked, utils::GlError};
+
+/// Which side of a stencil mask later draws are kept on.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum StencilTest {
+    /// Only draw where the mask was drawn, like a spotlight reveal.
+    #[default]
+    Inside,
+    /// Only draw where the mask wasn't drawn, like cutting a hole.
+    Outside,
+}
+
+/// Set the stencil comparison, see `glStencilFunc`.
+pub fn set_stencil_func(func: u32, reference: i32, mask: u32) -> Result<(), GlError> {
+    gl!(StencilFunc, func, reference, mask)
+}
+
+/// Set what happens to the stencil value when the stencil or depth test passes or fails,
+/// see `glStencilOp`.
+pub fn set_stencil_op(stencil_fail: u32, depth_fail: u32, pass: u32) -> Result<(), GlError> {
+    gl!(StencilOp, stencil_fail, depth_fail, pass)
+}
+
+/// Masks drawing to arbitrary shapes with the stencil buffer, for as long as it's alive.
+///
+/// Draws after `begin` only write the mask, call `apply` to start drawing the masked
+/// content. The stencil test is disabled again when the guard is dropped.
+pub struct StencilMask {
+    _private: (),
+}
+
+impl StencilMask {
+    /// Clear the stencil buffer and start writing the mask, colors aren't drawn.
+    pub fn begin() -> Result<Self, Error> {
+        gl!(Enable, gl::STENCIL_TEST)?;
+        gl!(StencilMask, 0xFF)?;
+        gl!(ClearStencil, 0)?;
+        gl!(Clear, gl::STENCIL_BUFFER_BIT)?;
+
+        set_stencil_func(gl::ALWAYS, 1, 0xFF)?;
+        set_stencil_op(gl::KEEP, gl::KEEP, gl::REPLACE)?;
+        gl!(ColorMask, gl::FALSE, gl::FALSE, gl::FALSE, gl::FALSE)?;
+
+        Ok(Self { _private: () })
+    }
+
+    /// Stop writing the mask and restrict the following draws to one side of it.
+    pub fn apply(&self, test: StencilTest) -> Result<(), Error> {
+        let func = match test {
+            StencilTest::Inside => gl::EQUAL,
+            StencilTest::Outside => gl::NOTEQUAL,
+        };
+
+        gl!(ColorMask, gl::TRUE, gl::TRUE, gl::TRUE, gl::TRUE)?;
+        gl!(StencilMask, 0x00)?;
+        set_stencil_func(func, 1, 0xFF)?;
+
+        Ok(())
+    }
+}
+
+impl Drop for StencilMask {
+    fn drop(&mut self) {
+        gl_unchecked!(ColorMask, gl::TRUE, gl::TRUE, gl::TRUE, gl::TRUE);
+        gl_unchecked!(StencilMask, 0xFF);
+        gl_unchecked!(StencilFunc, gl::ALWAYS, 0, 0xFF);
+        gl_unchecked!(StencilOp, gl::KEEP, gl::KEEP, gl::KEEP);
+        gl_unchecked!(Disable, gl::STENCIL_TEST);
+    }
+}
@@ -1,9 +1,25 @@
 #![allow(unused)]
 
+use std::ffi::CStr;
+
 use image::{GenericImageView, ImageError};
 
-use crate::{gl, rendering::TextureTrait};
+use super::GlState;
+use crate::{
+    error::Error,
+    gl, gl_unchecked,
+    rendering::{TextureFilter, TextureOptions, TextureTrait, TextureWrap},
+    utils::GlError,
+};
+
+// From GL_EXT_texture_filter_anisotropic, core since GL 4.6 but missing from the bindings.
+const TEXTURE_MAX_ANISOTROPY: u32 = 0x84FE;
+const MAX_TEXTURE_MAX_ANISOTROPY: u32 = 0x84FF;
 
+/// A 2D texture on the GPU.
+///
+/// The GL texture is deleted when the texture is dropped, so it has to be dropped before
+/// the window that owns the GL context.
 pub struct Texture {
     id: u32,
     width: u32,
@@ -33,157 +49,244 @@ impl Default for Texture {
 }
 
 impl Texture {
-    // pub fn new(
-    //     width: u32,
-    //     height: u32,
-    //     internal_format: u32,
-    //     image_format: u32,
-    //     wrap_s: u32,
-    //     wrap_t: u32,
-    //     filter_min: u32,
-    //     filter_max: u32,
-    // ) -> Result<Self, Box<dyn std::error::Error>> {
-    //     let mut id = 0;
-    //     gl!(GenTextures, 1, &mut id)?;
-    //     gl!(BindTexture, gl::TEXTURE_2D, id)?;
-    //     gl!(
-    //         TexImage2D,
-    //         gl::TEXTURE_2D,
-    //         0,
-    //         internal_format as i32,
-    //         width as i32,
-    //         height as i32,
-    //         0,
-    //         image_format,
-    //         gl::UNSIGNED_BYTE,
-    //         std::ptr::null(),
-    //     )?;
-    //     gl!(
-    //         TexParameteri,
-    //         gl::TEXTURE_2D,
-    //         gl::TEXTURE_WRAP_S,
-    //         wrap_s as i32
-    //     )?;
-    //     gl!(
-    //         TexParameteri,
-    //         gl::TEXTURE_2D,
-    //         gl::TEXTURE_WRAP_T,
-    //         wrap_t as i32
-    //     )?;
-    //     gl!(
-    //         TexParameteri,
-    //         gl::TEXTURE_2D,
-    //         gl::TEXTURE_MIN_FILTER,
-    //         filter_min as i32
-    //     )?;
-    //     gl!(
-    //         TexParameteri,
-    //         gl::TEXTURE_2D,
-    //         gl::TEXTURE_MAG_FILTER,
-    //         filter_max as i32
-    //     )?;
-    //     gl!(BindTexture, gl::TEXTURE_2D, 0)?;
-
-    //     Ok(Texture {
-    //         id,
-    //         width,
-    //         height,
-    //         internal_format,
-    //         image_format,
-    //         wrap_s,
-    //         wrap_t,
-    //         filter_min,
-    //         filter_max,
-    //     })
-    // }
-
-    pub fn generate_mipmaps(&self) {
-        gl!(BindTexture, gl::TEXTURE_2D, self.id);
-        gl!(GenerateMipmap, gl::TEXTURE_2D);
-    }
-
-    pub fn bind(&self) {
-        gl!(BindTexture, gl::TEXTURE_2D, self.id);
-    }
-
-    pub fn set_wrap_s(&mut self, wrap_s: u32) {
-        self.wrap_s = wrap_s;
-        gl!(BindTexture, gl::TEXTURE_2D, self.id);
+    /// Create an empty texture, generating mipmaps when `filter_min` is a mipmap filter.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        width: u32,
+        height: u32,
+        internal_format: u32,
+        image_format: u32,
+        wrap_s: u32,
+        wrap_t: u32,
+        filter_min: u32,
+        filter_max: u32,
+    ) -> Result<Self, Error> {
+        let mut id = 0;
+        gl!(GenTextures, 1, &mut id)?;
+        GlState::bind_texture(0, id)?;
+        gl!(
+            TexImage2D,
+            gl::TEXTURE_2D,
+            0,
+            internal_format as i32,
+            width as i32,
+            height as i32,
+            0,
+            image_format,
+            gl::UNSIGNED_BYTE,
+            std::ptr::null(),
+        )?;
+        if is_mipmap_filter(filter_min) {
+            gl!(GenerateMipmap, gl::TEXTURE_2D)?;
+        }
         gl!(
             TexParameteri,
             gl::TEXTURE_2D,
             gl::TEXTURE_WRAP_S,
             wrap_s as i32
-        );
-    }
-
-    pub fn set_wrap_t(&mut self, wrap_t: u32) {
-        self.wrap_t = wrap_t;
-        gl!(BindTexture, gl::TEXTURE_2D, self.id);
+        )?;
         gl!(
             TexParameteri,
             gl::TEXTURE_2D,
             gl::TEXTURE_WRAP_T,
             wrap_t as i32
-        );
-    }
-
-    pub fn set_filter_min(&mut self, filter_min: u32) {
-        self.filter_min = filter_min;
-        gl!(BindTexture, gl::TEXTURE_2D, self.id);
+        )?;
         gl!(
             TexParameteri,
             gl::TEXTURE_2D,
             gl::TEXTURE_MIN_FILTER,
             filter_min as i32
-        );
-    }
-
-    pub fn set_filter_max(&mut self, filter_max: u32) {
-        self.filter_max = filter_max;
-        gl!(BindTexture, gl::TEXTURE_2D, self.id);
+        )?;
         gl!(
             TexParameteri,
             gl::TEXTURE_2D,
             gl::TEXTURE_MAG_FILTER,
             filter_max as i32
-        );
+        )?;
+        GlState::bind_texture(0, 0)?;
+
+        Ok(Texture {
+            id,
+            width,
+            height,
+            internal_format,
+            image_format,
+            wrap_s,
+            wrap_t,
+            filter_min,
+            filter_max,
+        })
     }
 
-    pub fn set_filter(&mut self, filter_min: u32, filter_max: u32) {
-        self.filter_min = filter_min;
-        self.filter_max = filter_max;
-        gl!(BindTexture, gl::TEXTURE_2D, self.id);
-        gl!(
-            TexParameteri,
-            gl::TEXTURE_2D,
-            gl::TEXTURE_MIN_FILTER,
-            filter_min as i32
-        );
+    /// Generate mipmaps from the base level, switching to trilinear filtering if the
+    /// minifying filter doesn't use mipmaps yet.
+    pub fn generate_mipmaps(&mut self) -> Result<(), GlError> {
+        GlState::bind_texture(0, self.id)?;
+        gl!(GenerateMipmap, gl::TEXTURE_2D)?;
+
+        if !is_mipmap_filter(self.filter_min) {
+            self.filter_min = gl::LINEAR_MIPMAP_LINEAR;
+            gl!(
+                TexParameteri,
+                gl::TEXTURE_2D,
+                gl::TEXTURE_MIN_FILTER,
+                self.filter_min as i32
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Replace the pixels in a region of the texture, `data` is tightly packed in `format`.
+    pub fn update_region(
+        &self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        data: &[u8],
+        format: u32,
+    ) -> Result<(), Error> {
+        let fits = |start: u32, size: u32, limit: u32| {
+            start.checked_add(size).is_some_and(|end| end <= limit)
+        };
+        if !fits(x, width, self.width) || !fits(y, height, self.height) {
+            return Err(Error::InvalidArgument {
+                message: format!(
+                    "texture region {}x{} at ({}, {}) is outside of the {}x{} texture",
+                    width, height, x, y, self.width, self.height
+                ),
+            });
+        }
+
+        let bytes_per_pixel = bytes_per_pixel(format)?;
+        let expected = (width as usize)
+            .checked_mul(height as usize)
+            .and_then(|pixels| pixels.checked_mul(bytes_per_pixel))
+            .ok_or_else(|| Error::InvalidArgument {
+                message: format!("texture region {}x{} is too large", width, height),
+            })?;
+        if data.len() < expected {
+            return Err(Error::InvalidArgument {
+                message: format!(
+                    "texture region needs {} bytes of data but only {} were given",
+                    expected,
+                    data.len()
+                ),
+            });
+        }
+
+        GlState::bind_texture(0, self.id)?;
+
+        // The data is tightly packed, rows of RGB data aren't always 4 byte aligned.
+        gl!(PixelStorei, gl::UNPACK_ALIGNMENT, 1)?;
         gl!(
-            TexParameteri,
+            TexSubImage2D,
             gl::TEXTURE_2D,
-            gl::TEXTURE_MAG_FILTER,
-            filter_max as i32
-        );
+            0,
+            x as i32,
+            y as i32,
+            width as i32,
+            height as i32,
+            format,
+            gl::UNSIGNED_BYTE,
+            data.as_ptr() as *const _,
+        )?;
+        gl!(PixelStorei, gl::UNPACK_ALIGNMENT, 4)?;
+
+        GlState::bind_texture(0, 0)?;
+
+        Ok(())
     }
 
-    pub fn set_wrap(&mut self, wrap_s: u32, wrap_t: u32) {
-        self.wrap_s = wrap_s;
-        self.wrap_t = wrap_t;
-        gl!(BindTexture, gl::TEXTURE_2D, self.id);
+    /// Read the texture back from the GPU and save it as a PNG.
+    ///
+    /// Rows are saved in the order they are stored, so framebuffer textures come out
+    /// upside down.
+    pub fn save_png(&self, path: &str) -> Result<(), Error> {
+        let (format, color_type) = match self.internal_format {
+            gl::RGB => (gl::RGB, image::ColorType::Rgb8),
+            _ => (gl::RGBA, image::ColorType::Rgba8),
+        };
+
+        let mut pixels = vec![0u8; (self.width * self.height) as usize * bytes_per_pixel(format)?];
+
+        GlState::bind_texture(0, self.id)?;
+        gl!(PixelStorei, gl::PACK_ALIGNMENT, 1)?;
         gl!(
-            TexParameteri,
+            GetTexImage,
             gl::TEXTURE_2D,
-            gl::TEXTURE_WRAP_S,
-            wrap_s as i32
-        );
+            0,
+            format,
+            gl::UNSIGNED_BYTE,
+            pixels.as_mut_ptr() as *mut _,
+        )?;
+        gl!(PixelStorei, gl::PACK_ALIGNMENT, 4)?;
+        GlState::bind_texture(0, 0)?;
+
+        image::save_buffer(path, &pixels, self.width, self.height, color_type)?;
+
+        Ok(())
+    }
+
+    pub fn bind(&self) {
+        GlState::bind_texture(0, self.id);
+    }
+
+    pub fn set_wrap_s(&mut self, wrap_s: TextureWrap) -> Result<(), GlError> {
+        self.wrap_s = wrap_s.gl_enum();
+        self.set_parameter(gl::TEXTURE_WRAP_S, self.wrap_s)
+    }
+
+    pub fn set_wrap_t(&mut self, wrap_t: TextureWrap) -> Result<(), GlError> {
+        self.wrap_t = wrap_t.gl_enum();
+        self.set_parameter(gl::TEXTURE_WRAP_T, self.wrap_t)
+    }
+
+    /// Whether sampling past the edges repeats the texture, horizontally and vertically.
+    pub(crate) fn repeats(&self) -> (bool, bool) {
+        let repeats = |wrap| wrap == gl::REPEAT || wrap == gl::MIRRORED_REPEAT;
+        (repeats(self.wrap_s), repeats(self.wrap_t))
+    }
+
+    pub fn set_filter_min(&mut self, filter_min: TextureFilter) -> Result<(), GlError> {
+        self.filter_min = filter_min.gl_enum();
+        self.set_parameter(gl::TEXTURE_MIN_FILTER, self.filter_min)
+    }
+
+    /// Set the magnifying filter, only `Nearest` and `Linear` are valid.
+    pub fn set_filter_max(&mut self, filter_max: TextureFilter) -> Result<(), GlError> {
+        self.filter_max = filter_max.gl_enum();
+        self.set_parameter(gl::TEXTURE_MAG_FILTER, self.filter_max)
+    }
+
+    /// Set the level of anisotropic filtering, which keeps textures sharp when viewed at
+    /// oblique angles.
+    ///
+    /// The level is clamped to what the hardware supports, `1.0` turns it off. Does nothing
+    /// when anisotropic filtering isn't supported.
+    pub fn set_anisotropy(&mut self, level: f32) -> Result<(), GlError> {
+        if !anisotropy_supported()? {
+            return Ok(());
+        }
+
+        let mut max = 1.0;
+        gl!(GetFloatv, MAX_TEXTURE_MAX_ANISOTROPY, &mut max)?;
+
+        GlState::bind_texture(0, self.id)?;
         gl!(
-            TexParameteri,
+            TexParameterf,
             gl::TEXTURE_2D,
-            gl::TEXTURE_WRAP_T,
-            wrap_t as i32
-        );
+            TEXTURE_MAX_ANISOTROPY,
+            level.clamp(1.0, max)
+        )?;
+        GlState::bind_texture(0, 0)
+    }
+
+    fn set_parameter(&self, name: u32, value: u32) -> Result<(), GlError> {
+        GlState::bind_texture(0, self.id)?;
+        gl!(TexParameteri, gl::TEXTURE_2D, name, value as i32)?;
+        GlState::bind_texture(0, 0)
     }
 
     pub fn id(&self) -> u32 {
@@ -199,17 +302,61 @@ impl Texture {
     }
 }
 
+impl Drop for Texture {
+    fn drop(&mut self) {
+        // Zero is never a valid texture, like the default texture.
+        if self.id == 0 || !gl::DeleteTextures::is_loaded() {
+            return;
+        }
+
+        GlState::forget_texture(self.id);
+        gl_unchecked!(DeleteTextures, 1, &self.id);
+        self.id = 0;
+    }
+}
+
 impl TextureTrait for Texture {
-    fn from_file(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
-        // Get the image data
-        let image = image::open(path)?;
+    fn from_file_with(path: &str, options: TextureOptions) -> Result<Self, Error> {
+        // Get the image data as RGBA, whatever the format of the file is
+        let image = image::open(path)?.into_rgba8();
         let (width, height) = image.dimensions();
-        let data = image.as_bytes();
+
+        Self::from_memory_with(width, height, image.as_raw(), options)
+    }
+
+    fn from_memory_with(
+        width: u32,
+        height: u32,
+        data: &[u8],
+        options: TextureOptions,
+    ) -> Result<Self, Error> {
+        let expected = (width as usize)
+            .checked_mul(height as usize)
+            .and_then(|pixels| pixels.checked_mul(4))
+            .ok_or_else(|| Error::InvalidArgument {
+                message: format!("texture of {}x{} is too large", width, height),
+            })?;
+        if data.len() < expected {
+            return Err(Error::InvalidArgument {
+                message: format!(
+                    "texture of {}x{} needs {} bytes of RGBA data but only {} were given",
+                    width,
+                    height,
+                    expected,
+                    data.len()
+                ),
+            });
+        }
+
+        let wrap_s = options.wrap_s.gl_enum();
+        let wrap_t = options.wrap_t.gl_enum();
+        let filter_min = options.filter_min.gl_enum();
+        let filter_max = options.filter_mag.gl_enum();
 
         // Setup the texture
         let mut id = 0;
         gl!(GenTextures, 1, &mut id)?;
-        gl!(BindTexture, gl::TEXTURE_2D, id)?;
+        GlState::bind_texture(0, id)?;
         gl!(
             TexImage2D,
             gl::TEXTURE_2D,
@@ -222,43 +369,45 @@ impl TextureTrait for Texture {
             gl::UNSIGNED_BYTE,
             data.as_ptr() as *const _,
         )?;
-        gl!(GenerateMipmap, gl::TEXTURE_2D)?;
+        if options.mipmaps || is_mipmap_filter(filter_min) {
+            gl!(GenerateMipmap, gl::TEXTURE_2D)?;
+        }
         gl!(
             TexParameteri,
             gl::TEXTURE_2D,
             gl::TEXTURE_WRAP_S,
-            gl::REPEAT as i32
+            wrap_s as i32
         )?;
         gl!(
             TexParameteri,
             gl::TEXTURE_2D,
             gl::TEXTURE_WRAP_T,
-            gl::REPEAT as i32
+            wrap_t as i32
         )?;
         gl!(
             TexParameteri,
             gl::TEXTURE_2D,
             gl::TEXTURE_MIN_FILTER,
-            gl::LINEAR_MIPMAP_LINEAR as i32,
+            filter_min as i32,
         )?;
         gl!(
             TexParameteri,
             gl::TEXTURE_2D,
             gl::TEXTURE_MAG_FILTER,
-            gl::LINEAR as i32
+            filter_max as i32
         )?;
-        gl!(BindTexture, gl::TEXTURE_2D, 0)?;
+        GlState::bind_texture(0, 0)?;
 
         Ok(Texture {
             id,
             width,
             height,
-            internal_format: gl::RGBA as u32,
+            internal_format: gl::RGBA,
             image_format: gl::RGBA,
-            wrap_s: gl::REPEAT,
-            wrap_t: gl::REPEAT,
-            filter_min: gl::LINEAR,
-            filter_max: gl::LINEAR,
+            wrap_s,
+            wrap_t,
+            filter_min,
+            filter_max,
         })
     }
 
@@ -269,4 +418,87 @@ impl TextureTrait for Texture {
     fn height(&self) -> u32 {
         self.height
     }
+
+    fn set_filter(&mut self, min: TextureFilter, mag: TextureFilter) -> Result<(), Error> {
+        self.set_filter_min(min)?;
+        self.set_filter_max(mag)?;
+        Ok(())
+    }
+
+    fn set_wrap(&mut self, s: TextureWrap, t: TextureWrap) -> Result<(), Error> {
+        self.set_wrap_s(s)?;
+        self.set_wrap_t(t)?;
+        Ok(())
+    }
+}
+
+impl TextureFilter {
+    pub fn gl_enum(&self) -> u32 {
+        match self {
+            TextureFilter::Nearest => gl::NEAREST,
+            TextureFilter::Linear => gl::LINEAR,
+            TextureFilter::NearestMipmapNearest => gl::NEAREST_MIPMAP_NEAREST,
+            TextureFilter::LinearMipmapNearest => gl::LINEAR_MIPMAP_NEAREST,
+            TextureFilter::NearestMipmapLinear => gl::NEAREST_MIPMAP_LINEAR,
+            TextureFilter::LinearMipmapLinear => gl::LINEAR_MIPMAP_LINEAR,
+        }
+    }
+}
+
+impl TextureWrap {
+    pub fn gl_enum(&self) -> u32 {
+        match self {
+            TextureWrap::Repeat => gl::REPEAT,
+            TextureWrap::MirroredRepeat => gl::MIRRORED_REPEAT,
+            TextureWrap::ClampToEdge => gl::CLAMP_TO_EDGE,
+            TextureWrap::ClampToBorder => gl::CLAMP_TO_BORDER,
+        }
+    }
+}
+
+/// Check if a minifying filter samples from mipmaps.
+fn is_mipmap_filter(filter: u32) -> bool {
+    matches!(
+        filter,
+        gl::NEAREST_MIPMAP_NEAREST
+            | gl::LINEAR_MIPMAP_NEAREST
+            | gl::NEAREST_MIPMAP_LINEAR
+            | gl::LINEAR_MIPMAP_LINEAR
+    )
+}
+
+/// Check if the driver supports anisotropic filtering.
+fn anisotropy_supported() -> Result<bool, GlError> {
+    let mut count = 0;
+    gl!(GetIntegerv, gl::NUM_EXTENSIONS, &mut count)?;
+
+    for index in 0..count as u32 {
+        let name = gl!(GetStringi, gl::EXTENSIONS, index)?;
+        if name.is_null() {
+            continue;
+        }
+
+        let name = unsafe { CStr::from_ptr(name as *const _) };
+        if matches!(
+            name.to_bytes(),
+            b"GL_EXT_texture_filter_anisotropic" | b"GL_ARB_texture_filter_anisotropic"
+        ) {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+/// Get the size of a pixel in a pixel format with unsigned byte channels.
+fn bytes_per_pixel(format: u32) -> Result<usize, Error> {
+    match format {
+        gl::RED => Ok(1),
+        gl::RG => Ok(2),
+        gl::RGB | gl::BGR => Ok(3),
+        gl::RGBA | gl::BGRA => Ok(4),
+        _ => Err(Error::Unsupported {
+            message: format!("texture format {:#x}", format),
+        }),
+    }
 }
@@ -0,0 +1,216 @@
+use std::cell::RefCell;
+
+use nalgebra::Matrix4;
+
+use super::{
+    blend_mode::{disable_blending, enable_blending},
+    GlState, Shader, ShaderProgram, ShaderType, Texture,
+};
+use crate::{
+    error::Error,
+    gl, gl_unchecked,
+    rendering::{BlendMode, Color, TileMap, TileMapRendererTrait},
+};
+
+// Per vertex data, a vec2 position followed by a vec2 texture coordinate.
+const VERTEX_FLOATS: usize = 2 + 2;
+const TILE_FLOATS: usize = 6 * VERTEX_FLOATS;
+
+// Sample the whole texture, the texture coordinates are baked into the vertices.
+const FULL_REGION: (f32, f32, f32, f32) = (0.0, 0.0, 1.0, 1.0);
+
+/// Renders a tile map from a static vertex buffer of every tile.
+///
+/// The GL objects are deleted when the renderer is dropped, so it has to be dropped
+/// before the window that owns the GL context.
+pub struct TileMapRenderer {
+    map: TileMap<Texture>,
+    shader: ShaderProgram,
+    vao: u32,
+    vbo: u32,
+    // The first vertex and vertex count of each visible row, reused between frames.
+    ranges: RefCell<(Vec<i32>, Vec<i32>)>,
+}
+
+impl TileMapRenderer {
+    /// Build the two triangles of a tile, empty tiles are degenerate.
+    fn tile_vertices(map: &TileMap<Texture>, column: u32, row: u32) -> [f32; TILE_FLOATS] {
+        let region = match map.tile(column, row).and_then(|tile| map.tile_region(tile)) {
+            Some(region) => region,
+            None => return [0.0; TILE_FLOATS],
+        };
+
+        let bounds = map.tile_bounds(column, row);
+        let texture = map.atlas().texture();
+        let (width, height) = (texture.width() as f32, texture.height() as f32);
+
+        let (left, top) = (bounds.x, bounds.y);
+        let (right, bottom) = (bounds.x + bounds.width, bounds.y + bounds.height);
+        let (u0, v0) = (region.x / width, region.y / height);
+        let (u1, v1) = (
+            (region.x + region.width) / width,
+            (region.y + region.height) / height,
+        );
+
+        [
+            left, bottom, u0, v1, //
+            right, top, u1, v0, //
+            left, top, u0, v0, //
+            left, bottom, u0, v1, //
+            right, bottom, u1, v1, //
+            right, top, u1, v0, //
+        ]
+    }
+}
+
+impl TileMapRendererTrait for TileMapRenderer {
+    type Texture = Texture;
+
+    fn new(map: TileMap<Texture>) -> Result<Self, Error> {
+        // Tiles are drawn with the sprite shader, the transform is the view projection.
+        let shader = ShaderProgram::from_shaders(&[
+            Shader::from_source(
+                ShaderType::Vertex,
+                include_str!("../../../assets/shaders/sprite.vert"),
+            )?,
+            Shader::from_source(
+                ShaderType::Fragment,
+                include_str!("../../../assets/shaders/sprite.frag"),
+            )?,
+        ])?;
+
+        let mut vertices = Vec::with_capacity((map.columns() * map.rows()) as usize * TILE_FLOATS);
+        for row in 0..map.rows() {
+            for column in 0..map.columns() {
+                vertices.extend_from_slice(&Self::tile_vertices(&map, column, row));
+            }
+        }
+
+        let mut vao = 0;
+        let mut vbo = 0;
+
+        gl!(GenVertexArrays, 1, &mut vao)?;
+        GlState::bind_vertex_array(vao)?;
+        gl!(GenBuffers, 1, &mut vbo)?;
+        gl!(BindBuffer, gl::ARRAY_BUFFER, vbo)?;
+        gl!(
+            BufferData,
+            gl::ARRAY_BUFFER,
+            std::mem::size_of_val(vertices.as_slice()) as isize,
+            vertices.as_ptr() as *const _,
+            gl::STATIC_DRAW,
+        )?;
+
+        let stride = (VERTEX_FLOATS * std::mem::size_of::<f32>()) as i32;
+        gl!(
+            VertexAttribPointer,
+            0,
+            2,
+            gl::FLOAT,
+            gl::FALSE,
+            stride,
+            std::ptr::null(),
+        )?;
+        gl!(EnableVertexAttribArray, 0)?;
+        gl!(
+            VertexAttribPointer,
+            1,
+            2,
+            gl::FLOAT,
+            gl::FALSE,
+            stride,
+            (2 * std::mem::size_of::<f32>()) as *const _,
+        )?;
+        gl!(EnableVertexAttribArray, 1)?;
+
+        gl!(BindBuffer, gl::ARRAY_BUFFER, 0)?;
+        GlState::bind_vertex_array(0)?;
+
+        Ok(Self {
+            map,
+            shader,
+            vao,
+            vbo,
+            ranges: RefCell::new((Vec::new(), Vec::new())),
+        })
+    }
+
+    fn map(&self) -> &TileMap<Texture> {
+        &self.map
+    }
+
+    fn set_tile(&mut self, column: u32, row: u32, tile: Option<usize>) -> Result<(), Error> {
+        self.map.set_tile(column, row, tile)?;
+
+        let vertices = Self::tile_vertices(&self.map, column, row);
+        let offset =
+            (row * self.map.columns() + column) as usize * std::mem::size_of_val(&vertices);
+
+        gl!(BindBuffer, gl::ARRAY_BUFFER, self.vbo)?;
+        gl!(
+            BufferSubData,
+            gl::ARRAY_BUFFER,
+            offset as isize,
+            std::mem::size_of_val(&vertices) as isize,
+            vertices.as_ptr() as *const _,
+        )?;
+        gl!(BindBuffer, gl::ARRAY_BUFFER, 0)?;
+
+        Ok(())
+    }
+
+    fn draw(&self, projection: &Matrix4<f32>, view: &Matrix4<f32>) -> Result<(), Error> {
+        let (columns, rows) = self.map.visible_tiles(projection, view);
+        if columns.is_empty() || rows.is_empty() {
+            return Ok(());
+        }
+
+        // Each visible row is a contiguous run of tiles in the buffer.
+        let mut ranges = self.ranges.borrow_mut();
+        let (firsts, counts) = &mut *ranges;
+        firsts.clear();
+        counts.clear();
+        for row in rows {
+            let first = (row * self.map.columns() + columns.start) as usize * 6;
+            firsts.push(first as i32);
+            counts.push((columns.len() * 6) as i32);
+        }
+
+        enable_blending(BlendMode::Alpha)?;
+
+        self.shader.use_program();
+        self.shader
+            .set_uniform_mat4("transform", &(projection * view))?;
+        self.shader
+            .set_uniform_4f("spriteColor", Color::WHITE.into())?;
+        self.shader.set_uniform_4f("textureRegion", FULL_REGION)?;
+
+        GlState::bind_vertex_array(self.vao)?;
+        GlState::bind_texture(0, self.map.atlas().texture().id())?;
+
+        gl!(
+            MultiDrawArrays,
+            gl::TRIANGLES,
+            firsts.as_ptr(),
+            counts.as_ptr(),
+            firsts.len() as i32
+        )?;
+
+        disable_blending()?;
+
+        Ok(())
+    }
+}
+
+impl Drop for TileMapRenderer {
+    fn drop(&mut self) {
+        // Nothing to free if GL was never loaded.
+        if !gl::DeleteVertexArrays::is_loaded() {
+            return;
+        }
+
+        GlState::forget_vertex_array(self.vao);
+        gl_unchecked!(DeleteVertexArrays, 1, &self.vao);
+        gl_unchecked!(DeleteBuffers, 1, &self.vbo);
+    }
+}
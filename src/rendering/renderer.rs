@@ -0,0 +1,45 @@
+use crate::{
+    error::Error,
+    rendering::{
+        LineRendererTrait, ShapeRendererTrait, SpriteRendererTrait, TextureTrait, TileMap,
+        TileMapRendererTrait,
+    },
+};
+
+/// A graphics backend, tying together the types that implement each renderer trait.
+///
+/// Code generic over a `Renderer` works with any backend, the backend built by default is
+/// selected with a cargo feature and exported as `DefaultRenderer`.
+pub trait Renderer {
+    type Texture: TextureTrait;
+    type Shader;
+    type SpriteRenderer: SpriteRendererTrait<Texture = Self::Texture>;
+    type ShapeRenderer: ShapeRendererTrait;
+    type LineRenderer: LineRendererTrait;
+    type TileMapRenderer: TileMapRendererTrait<Texture = Self::Texture>;
+
+    /// Compile a shader from the backend's shading language.
+    fn create_shader(vertex_source: &str, fragment_source: &str) -> Result<Self::Shader, Error>;
+
+    fn create_texture(path: &str) -> Result<Self::Texture, Error> {
+        Self::Texture::from_file(path)
+    }
+
+    fn create_sprite_renderer() -> Result<Self::SpriteRenderer, Error> {
+        Self::SpriteRenderer::new()
+    }
+
+    fn create_shape_renderer() -> Result<Self::ShapeRenderer, Error> {
+        Self::ShapeRenderer::new()
+    }
+
+    fn create_line_renderer() -> Result<Self::LineRenderer, Error> {
+        Self::LineRenderer::new()
+    }
+
+    fn create_tile_map_renderer(
+        map: TileMap<Self::Texture>,
+    ) -> Result<Self::TileMapRenderer, Error> {
+        Self::TileMapRenderer::new(map)
+    }
+}
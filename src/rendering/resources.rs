@@ -0,0 +1,71 @@
+use std::{collections::HashMap, rc::Rc};
+
+use crate::{error::Error, rendering::TextureTrait};
+
+/// A cache of loaded textures keyed by path, so each file is only uploaded once.
+///
+/// Textures are freed when the cache and every handle to them have been dropped.
+pub struct Resources<T> {
+    textures: HashMap<String, Rc<T>>,
+}
+
+impl<T: TextureTrait> Default for Resources<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: TextureTrait> Resources<T> {
+    pub fn new() -> Self {
+        Self {
+            textures: HashMap::new(),
+        }
+    }
+
+    /// Get the texture at `path`, loading it on the first request.
+    pub fn texture(&mut self, path: &str) -> Result<Rc<T>, Error> {
+        if let Some(texture) = self.textures.get(path) {
+            return Ok(Rc::clone(texture));
+        }
+
+        self.reload(path)
+    }
+
+    /// Load the texture at `path` again, replacing the cached one.
+    ///
+    /// Handles to the old texture stay valid, only later requests get the new one.
+    pub fn reload(&mut self, path: &str) -> Result<Rc<T>, Error> {
+        let texture = Rc::new(T::from_file(path)?);
+        self.textures.insert(path.to_string(), Rc::clone(&texture));
+
+        Ok(texture)
+    }
+
+    /// Drop the cached handle to a texture, returning whether it was cached.
+    pub fn remove(&mut self, path: &str) -> bool {
+        self.textures.remove(path).is_some()
+    }
+
+    /// Drop the cached handles to textures that are no longer used anywhere else.
+    pub fn remove_unused(&mut self) {
+        self.textures
+            .retain(|_, texture| Rc::strong_count(texture) > 1);
+    }
+
+    pub fn contains(&self, path: &str) -> bool {
+        self.textures.contains_key(path)
+    }
+
+    pub fn len(&self) -> usize {
+        self.textures.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.textures.is_empty()
+    }
+
+    /// Drop every cached handle.
+    pub fn clear(&mut self) {
+        self.textures.clear();
+    }
+}
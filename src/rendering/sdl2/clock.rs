@@ -0,0 +1,70 @@
+use sdl2::TimerSubsystem;
+
+use super::WindowError;
+
+// The number of frames averaged for the fps.
+const FPS_SAMPLES: usize = 60;
+
+/// Measures the time between frames with the high resolution performance counter.
+pub struct Clock {
+    timer: TimerSubsystem,
+    frequency: f64,
+    start: u64,
+    last: u64,
+    delta: f32,
+    frame_times: [f32; FPS_SAMPLES],
+    frame_index: usize,
+    frame_count: usize,
+}
+
+impl Clock {
+    pub fn new(sdl_context: &sdl2::Sdl) -> Result<Self, WindowError> {
+        let timer = sdl_context.timer()?;
+        let now = timer.performance_counter();
+
+        Ok(Self {
+            frequency: timer.performance_frequency() as f64,
+            timer,
+            start: now,
+            last: now,
+            delta: 0.0,
+            frame_times: [0.0; FPS_SAMPLES],
+            frame_index: 0,
+            frame_count: 0,
+        })
+    }
+
+    /// Start a new frame, returning the seconds since the previous call.
+    pub fn tick(&mut self) -> f32 {
+        let now = self.timer.performance_counter();
+        self.delta = ((now - self.last) as f64 / self.frequency) as f32;
+        self.last = now;
+
+        self.frame_times[self.frame_index] = self.delta;
+        self.frame_index = (self.frame_index + 1) % FPS_SAMPLES;
+        self.frame_count = (self.frame_count + 1).min(FPS_SAMPLES);
+
+        self.delta
+    }
+
+    /// The seconds between the last two ticks, multiply movement by this for framerate
+    /// independent motion.
+    pub fn delta(&self) -> f32 {
+        self.delta
+    }
+
+    /// The frames per second, averaged over the last 60 frames.
+    pub fn fps(&self) -> f32 {
+        let total: f32 = self.frame_times[..self.frame_count].iter().sum();
+        if total <= 0.0 {
+            return 0.0;
+        }
+
+        self.frame_count as f32 / total
+    }
+
+    /// The seconds since the clock was created.
+    pub fn elapsed(&self) -> f32 {
+        ((self.timer.performance_counter() - self.start) as f64 / self.frequency) as f32
+    }
+}
@@ -0,0 +1,189 @@
+use nalgebra::Vector2;
+use sdl2::{
+    event::{Event as SdlEvent, WindowEvent},
+    keyboard::Keycode,
+    mouse::{MouseButton as SdlMouseButton, MouseWheelDirection},
+};
+
+use crate::rendering::{Event, Key, MouseButton};
+
+/// Translate the SDL events with a babo equivalent.
+///
+/// Gamepad connections are translated by the window, which knows the id of the opened gamepad.
+pub(crate) fn translate_event(event: &SdlEvent) -> Option<Event> {
+    let event = match event {
+        SdlEvent::Quit { .. } => Event::Quit,
+        SdlEvent::KeyDown {
+            keycode: Some(keycode),
+            repeat,
+            ..
+        } => Event::KeyDown {
+            key: translate_key(*keycode),
+            repeat: *repeat,
+        },
+        SdlEvent::KeyUp {
+            keycode: Some(keycode),
+            ..
+        } => Event::KeyUp {
+            key: translate_key(*keycode),
+        },
+        SdlEvent::TextInput { text, .. } => Event::TextInput { text: text.clone() },
+        SdlEvent::MouseMotion {
+            x, y, xrel, yrel, ..
+        } => Event::MouseMove {
+            position: Vector2::new(*x, *y),
+            delta: Vector2::new(*xrel, *yrel),
+        },
+        SdlEvent::MouseButtonDown {
+            mouse_btn, x, y, ..
+        } => Event::MouseDown {
+            button: translate_mouse_button(*mouse_btn)?,
+            position: Vector2::new(*x, *y),
+        },
+        SdlEvent::MouseButtonUp {
+            mouse_btn, x, y, ..
+        } => Event::MouseUp {
+            button: translate_mouse_button(*mouse_btn)?,
+            position: Vector2::new(*x, *y),
+        },
+        SdlEvent::MouseWheel {
+            x, y, direction, ..
+        } => {
+            // Flipped wheels report inverted values, normalize them so positive is up and right.
+            let sign = match direction {
+                MouseWheelDirection::Flipped => -1.0,
+                _ => 1.0,
+            };
+
+            Event::MouseWheel {
+                delta: Vector2::new(*x as f32, *y as f32) * sign,
+            }
+        }
+        SdlEvent::Window { win_event, .. } => match win_event {
+            // Sent for every size change, unlike `Resized` which is only sent for external ones.
+            WindowEvent::SizeChanged(width, height) => Event::Resized {
+                width: (*width).max(0) as u32,
+                height: (*height).max(0) as u32,
+            },
+            WindowEvent::FocusGained => Event::FocusGained,
+            WindowEvent::FocusLost => Event::FocusLost,
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    Some(event)
+}
+
+fn translate_key(keycode: Keycode) -> Key {
+    match keycode {
+        Keycode::A => Key::A,
+        Keycode::B => Key::B,
+        Keycode::C => Key::C,
+        Keycode::D => Key::D,
+        Keycode::E => Key::E,
+        Keycode::F => Key::F,
+        Keycode::G => Key::G,
+        Keycode::H => Key::H,
+        Keycode::I => Key::I,
+        Keycode::J => Key::J,
+        Keycode::K => Key::K,
+        Keycode::L => Key::L,
+        Keycode::M => Key::M,
+        Keycode::N => Key::N,
+        Keycode::O => Key::O,
+        Keycode::P => Key::P,
+        Keycode::Q => Key::Q,
+        Keycode::R => Key::R,
+        Keycode::S => Key::S,
+        Keycode::T => Key::T,
+        Keycode::U => Key::U,
+        Keycode::V => Key::V,
+        Keycode::W => Key::W,
+        Keycode::X => Key::X,
+        Keycode::Y => Key::Y,
+        Keycode::Z => Key::Z,
+        Keycode::Num0 => Key::Num0,
+        Keycode::Num1 => Key::Num1,
+        Keycode::Num2 => Key::Num2,
+        Keycode::Num3 => Key::Num3,
+        Keycode::Num4 => Key::Num4,
+        Keycode::Num5 => Key::Num5,
+        Keycode::Num6 => Key::Num6,
+        Keycode::Num7 => Key::Num7,
+        Keycode::Num8 => Key::Num8,
+        Keycode::Num9 => Key::Num9,
+        Keycode::F1 => Key::F1,
+        Keycode::F2 => Key::F2,
+        Keycode::F3 => Key::F3,
+        Keycode::F4 => Key::F4,
+        Keycode::F5 => Key::F5,
+        Keycode::F6 => Key::F6,
+        Keycode::F7 => Key::F7,
+        Keycode::F8 => Key::F8,
+        Keycode::F9 => Key::F9,
+        Keycode::F10 => Key::F10,
+        Keycode::F11 => Key::F11,
+        Keycode::F12 => Key::F12,
+        Keycode::Escape => Key::Escape,
+        Keycode::Return => Key::Enter,
+        Keycode::KpEnter => Key::Enter,
+        Keycode::Space => Key::Space,
+        Keycode::Tab => Key::Tab,
+        Keycode::Backspace => Key::Backspace,
+        Keycode::Delete => Key::Delete,
+        Keycode::Insert => Key::Insert,
+        Keycode::Home => Key::Home,
+        Keycode::End => Key::End,
+        Keycode::PageUp => Key::PageUp,
+        Keycode::PageDown => Key::PageDown,
+        Keycode::Left => Key::Left,
+        Keycode::Right => Key::Right,
+        Keycode::Up => Key::Up,
+        Keycode::Down => Key::Down,
+        Keycode::LShift => Key::LeftShift,
+        Keycode::RShift => Key::RightShift,
+        Keycode::LCtrl => Key::LeftCtrl,
+        Keycode::RCtrl => Key::RightCtrl,
+        Keycode::LAlt => Key::LeftAlt,
+        Keycode::RAlt => Key::RightAlt,
+        Keycode::LGui => Key::LeftSuper,
+        Keycode::RGui => Key::RightSuper,
+        Keycode::CapsLock => Key::CapsLock,
+        Keycode::Minus => Key::Minus,
+        Keycode::Equals => Key::Equals,
+        Keycode::LeftBracket => Key::LeftBracket,
+        Keycode::RightBracket => Key::RightBracket,
+        Keycode::Backslash => Key::Backslash,
+        Keycode::Semicolon => Key::Semicolon,
+        Keycode::Quote => Key::Quote,
+        Keycode::Backquote => Key::Backquote,
+        Keycode::Comma => Key::Comma,
+        Keycode::Period => Key::Period,
+        Keycode::Slash => Key::Slash,
+        _ => Key::Unknown,
+    }
+}
+
+fn translate_mouse_button(button: SdlMouseButton) -> Option<MouseButton> {
+    match button {
+        SdlMouseButton::Left => Some(MouseButton::Left),
+        SdlMouseButton::Middle => Some(MouseButton::Middle),
+        SdlMouseButton::Right => Some(MouseButton::Right),
+        SdlMouseButton::X1 => Some(MouseButton::X1),
+        SdlMouseButton::X2 => Some(MouseButton::X2),
+        SdlMouseButton::Unknown => None,
+    }
+}
+
+impl From<MouseButton> for SdlMouseButton {
+    fn from(button: MouseButton) -> Self {
+        match button {
+            MouseButton::Left => SdlMouseButton::Left,
+            MouseButton::Middle => SdlMouseButton::Middle,
+            MouseButton::Right => SdlMouseButton::Right,
+            MouseButton::X1 => SdlMouseButton::X1,
+            MouseButton::X2 => SdlMouseButton::X2,
+        }
+    }
+}
@@ -0,0 +1,99 @@
+use sdl2::controller::{Axis, Button, GameController};
+
+use crate::rendering::{GamepadAxis, GamepadButton};
+
+/// A connected game controller, opened by the window when it's plugged in.
+pub struct Gamepad {
+    controller: GameController,
+    deadzone: f32,
+}
+
+impl Gamepad {
+    // Sticks rarely rest exactly at zero.
+    const DEFAULT_DEADZONE: f32 = 0.15;
+
+    pub(crate) fn new(controller: GameController) -> Self {
+        Self {
+            controller,
+            deadzone: Self::DEFAULT_DEADZONE,
+        }
+    }
+
+    /// The instance id, matching the `which` of controller events.
+    pub fn id(&self) -> u32 {
+        self.controller.instance_id()
+    }
+
+    pub fn name(&self) -> String {
+        self.controller.name()
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.controller.attached()
+    }
+
+    pub fn button(&self, button: GamepadButton) -> bool {
+        self.controller.button(translate_button(button))
+    }
+
+    /// The position of an axis in the range [-1, 1], triggers are in the range [0, 1].
+    ///
+    /// Values inside of the deadzone are zero, the rest of the range is rescaled so it still
+    /// starts at zero.
+    pub fn axis(&self, axis: GamepadAxis) -> f32 {
+        let value =
+            (self.controller.axis(translate_axis(axis)) as f32 / i16::MAX as f32).clamp(-1.0, 1.0);
+
+        if value.abs() <= self.deadzone {
+            return 0.0;
+        }
+
+        value.signum() * (value.abs() - self.deadzone) / (1.0 - self.deadzone)
+    }
+
+    pub fn deadzone(&self) -> f32 {
+        self.deadzone
+    }
+
+    /// Set the fraction of each axis ignored around the center.
+    pub fn set_deadzone(&mut self, deadzone: f32) {
+        self.deadzone = deadzone.clamp(0.0, 0.99);
+    }
+}
+
+fn translate_button(button: GamepadButton) -> Button {
+    match button {
+        GamepadButton::A => Button::A,
+        GamepadButton::B => Button::B,
+        GamepadButton::X => Button::X,
+        GamepadButton::Y => Button::Y,
+        GamepadButton::Back => Button::Back,
+        GamepadButton::Guide => Button::Guide,
+        GamepadButton::Start => Button::Start,
+        GamepadButton::LeftStick => Button::LeftStick,
+        GamepadButton::RightStick => Button::RightStick,
+        GamepadButton::LeftShoulder => Button::LeftShoulder,
+        GamepadButton::RightShoulder => Button::RightShoulder,
+        GamepadButton::DPadUp => Button::DPadUp,
+        GamepadButton::DPadDown => Button::DPadDown,
+        GamepadButton::DPadLeft => Button::DPadLeft,
+        GamepadButton::DPadRight => Button::DPadRight,
+        GamepadButton::Misc => Button::Misc1,
+        GamepadButton::Paddle1 => Button::Paddle1,
+        GamepadButton::Paddle2 => Button::Paddle2,
+        GamepadButton::Paddle3 => Button::Paddle3,
+        GamepadButton::Paddle4 => Button::Paddle4,
+        GamepadButton::Touchpad => Button::Touchpad,
+    }
+}
+
+fn translate_axis(axis: GamepadAxis) -> Axis {
+    match axis {
+        GamepadAxis::LeftX => Axis::LeftX,
+        GamepadAxis::LeftY => Axis::LeftY,
+        GamepadAxis::RightX => Axis::RightX,
+        GamepadAxis::RightY => Axis::RightY,
+        GamepadAxis::LeftTrigger => Axis::TriggerLeft,
+        GamepadAxis::RightTrigger => Axis::TriggerRight,
+    }
+}
@@ -1,3 +1,8 @@
+pub mod clock;
+mod event;
+pub mod gamepad;
 pub mod window;
 
+pub use clock::*;
+pub use gamepad::*;
 pub use window::*;
@@ -1,7 +1,22 @@
-use sdl2::event::Event;
+use std::time::{Duration, Instant};
+
+use nalgebra::Vector2;
+use sdl2::{
+    event::Event as SdlEvent,
+    video::{FullscreenType, SwapInterval},
+};
 use snafu::Snafu;
 
-use crate::{gl_unchecked, rendering::WindowTrait};
+use super::{event::translate_event, Clock, Gamepad};
+use crate::{
+    error::Error,
+    gl, gl_unchecked,
+    rendering::{
+        CullFace, DepthFunc, Event, FrontFace, FullscreenMode, Input, MouseButton, VSync,
+        WindowOptions, WindowTrait,
+    },
+    utils::check_gl_error,
+};
 
 #[derive(Debug, Snafu)]
 pub enum WindowError {
@@ -23,11 +38,49 @@ pub struct Window {
     _sdl_context: sdl2::Sdl,
     _gl_context: sdl2::video::GLContext,
     event_pump: sdl2::EventPump,
+    controller_subsystem: sdl2::GameControllerSubsystem,
+    gamepads: Vec<Gamepad>,
     should_close: bool,
+    resized: bool,
+    samples: u8,
+    depth_test: bool,
+    depth_func: DepthFunc,
+    cull_face: Option<CullFace>,
+    front_face: FrontFace,
+    clock: Clock,
+    input: Input,
 }
 
 impl Window {
     pub fn new(width: u32, height: u32, title: &str) -> Result<Self, WindowError> {
+        Self::with_options(width, height, title, WindowOptions::default())
+    }
+
+    /// Create a window with extra context settings, like multisampling.
+    pub fn with_options(
+        width: u32,
+        height: u32,
+        title: &str,
+        options: WindowOptions,
+    ) -> Result<Self, WindowError> {
+        Self::create(width, height, title, false, options)
+    }
+
+    /// Create a hidden window with a GL context, for rendering offscreen in tests and tools.
+    ///
+    /// Nothing is shown on screen, render into a `Framebuffer` and read it back to inspect
+    /// the output. A display (or a virtual one like Xvfb) is still required.
+    pub fn headless(width: u32, height: u32) -> Result<Self, WindowError> {
+        Self::create(width, height, "", true, WindowOptions::default())
+    }
+
+    fn create(
+        width: u32,
+        height: u32,
+        title: &str,
+        hidden: bool,
+        options: WindowOptions,
+    ) -> Result<Self, WindowError> {
         let _sdl_context = sdl2::init()?;
 
         let video_subsystem = _sdl_context.video()?;
@@ -37,20 +90,48 @@ impl Window {
             .set_context_profile(sdl2::video::GLProfile::Core);
         video_subsystem.gl_attr().set_context_version(3, 3);
         video_subsystem.gl_attr().set_context_flags().debug().set();
+        video_subsystem.gl_attr().set_depth_size(24);
+        video_subsystem.gl_attr().set_stencil_size(8);
+
+        if options.samples > 1 {
+            video_subsystem.gl_attr().set_multisample_buffers(1);
+            video_subsystem
+                .gl_attr()
+                .set_multisample_samples(options.samples);
+        }
 
-        let window = video_subsystem
-            .window(title, width, height)
+        let mut builder = video_subsystem.window(title, width, height);
+        builder
             .position_centered()
-            .opengl()
-            .build()
-            .map_err(|err| err.to_string())?;
+            .resizable()
+            .allow_highdpi()
+            .opengl();
+
+        if hidden {
+            builder.hidden();
+        }
+
+        let window = builder.build().map_err(|err| err.to_string())?;
 
         let _gl_context = window.gl_create_context()?;
         gl::load_with(|s| video_subsystem.gl_get_proc_address(s) as *const _);
 
+        // The new context is current, nothing cached from a previous one is bound in it.
+        #[cfg(feature = "opengl")]
+        crate::rendering::opengl::GlState::reset();
+
         video_subsystem.gl_set_swap_interval(1)?;
 
+        // The driver may clamp the sample count, so ask for what was actually created.
+        let mut samples = 0;
+        gl_unchecked!(GetIntegerv, gl::SAMPLES, &mut samples);
+        if samples > 1 {
+            gl_unchecked!(Enable, gl::MULTISAMPLE);
+        }
+
         let event_pump = _sdl_context.event_pump()?;
+        let controller_subsystem = _sdl_context.game_controller()?;
+        let clock = Clock::new(&_sdl_context)?;
 
         Ok(Window {
             width,
@@ -60,18 +141,229 @@ impl Window {
             _sdl_context,
             _gl_context,
             event_pump,
+            controller_subsystem,
+            gamepads: Vec::new(),
             should_close: false,
+            resized: false,
+            samples: samples as u8,
+            depth_test: false,
+            depth_func: DepthFunc::default(),
+            cull_face: None,
+            front_face: FrontFace::default(),
+            clock,
+            input: Input::new(),
         })
     }
+
+    /// The number of MSAA samples per pixel of the window, 0 when multisampling is off.
+    pub fn samples(&self) -> u8 {
+        self.samples
+    }
+
+    pub fn clock(&self) -> &Clock {
+        &self.clock
+    }
+
+    /// The keyboard state as of the last `events`.
+    pub fn input(&self) -> &Input {
+        &self.input
+    }
+
+    /// The connected gamepads, controllers connected at startup are added by the first
+    /// call to `events`.
+    pub fn gamepads(&self) -> &[Gamepad] {
+        &self.gamepads
+    }
+
+    pub fn gamepads_mut(&mut self) -> &mut [Gamepad] {
+        &mut self.gamepads
+    }
+
+    /// Open and close gamepads as they're connected and disconnected.
+    fn handle_controller_event(&mut self, event: &SdlEvent) -> Option<Event> {
+        match event {
+            SdlEvent::ControllerDeviceAdded { which, .. } => {
+                // Devices that can't be opened are skipped, they still show up as joysticks.
+                let gamepad = Gamepad::new(self.controller_subsystem.open(*which).ok()?);
+                let id = gamepad.id();
+                self.gamepads.push(gamepad);
+
+                Some(Event::GamepadConnected { id })
+            }
+            SdlEvent::ControllerDeviceRemoved { which, .. } => {
+                self.gamepads.retain(|gamepad| gamepad.id() != *which);
+
+                Some(Event::GamepadDisconnected { id: *which })
+            }
+            _ => None,
+        }
+    }
+
+    /// The mouse position in window pixels with a top-left origin, as of the last `events`.
+    pub fn mouse_position(&self) -> Vector2<i32> {
+        let state = self.event_pump.mouse_state();
+        Vector2::new(state.x(), state.y())
+    }
+
+    /// Whether a mouse button is held, as of the last `events`.
+    pub fn mouse_down(&self, button: MouseButton) -> bool {
+        self.event_pump
+            .mouse_state()
+            .is_mouse_button_pressed(button.into())
+    }
+
+    /// Hide the cursor and lock it to the window, only the `delta` of `Event::MouseMove`
+    /// is meaningful while enabled.
+    ///
+    /// The OS cursor is hidden and warped back to the window, so it doesn't move while grabbed.
+    pub fn set_relative_mouse(&mut self, relative: bool) {
+        self._sdl_context.mouse().set_relative_mouse_mode(relative);
+    }
+
+    pub fn relative_mouse(&self) -> bool {
+        self._sdl_context.mouse().relative_mouse_mode()
+    }
+
+    pub fn set_cursor_visible(&mut self, visible: bool) {
+        self._sdl_context.mouse().show_cursor(visible);
+    }
+
+    /// Iterate over the pending events without collecting them, like `events`.
+    ///
+    /// Starts a new input frame, the window state is updated as the events are iterated.
+    pub fn poll_events(&mut self) -> impl Iterator<Item = Event> + '_ {
+        self.begin_frame();
+
+        std::iter::from_fn(move || loop {
+            let event = self.event_pump.poll_event()?;
+            if let Some(event) = self.handle_event(&event) {
+                return Some(event);
+            }
+        })
+    }
+
+    /// Block until an event arrives, for apps that only redraw on input.
+    ///
+    /// Starts a new input frame like `poll_events`, so don't mix it with polling in the
+    /// same frame.
+    pub fn wait_event(&mut self) -> Event {
+        self.begin_frame();
+
+        loop {
+            let event = self.event_pump.wait_event();
+            if let Some(event) = self.handle_event(&event) {
+                return event;
+            }
+        }
+    }
+
+    /// Block until an event arrives or `timeout` milliseconds pass, returning `None` on timeout.
+    pub fn wait_event_timeout(&mut self, timeout: u32) -> Option<Event> {
+        self.begin_frame();
+
+        let deadline = Instant::now() + Duration::from_millis(timeout as u64);
+
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let event = self
+                .event_pump
+                .wait_event_timeout(remaining.as_millis() as u32)?;
+
+            if let Some(event) = self.handle_event(&event) {
+                return Some(event);
+            }
+        }
+    }
+
+    /// Save what has been drawn this frame as a PNG at the drawable resolution.
+    ///
+    /// Reads the back buffer, so call it before `present`.
+    pub fn screenshot(&self, path: &str) -> Result<(), Error> {
+        let (width, height) = self.drawable_size();
+        let stride = width as usize * 3;
+        let mut pixels = vec![0u8; stride * height as usize];
+
+        let mut previous_framebuffer = 0;
+        gl!(
+            GetIntegerv,
+            gl::READ_FRAMEBUFFER_BINDING,
+            &mut previous_framebuffer
+        )?;
+        gl!(BindFramebuffer, gl::READ_FRAMEBUFFER, 0)?;
+        gl!(PixelStorei, gl::PACK_ALIGNMENT, 1)?;
+        gl!(
+            ReadPixels,
+            0,
+            0,
+            width as i32,
+            height as i32,
+            gl::RGB,
+            gl::UNSIGNED_BYTE,
+            pixels.as_mut_ptr() as *mut _,
+        )?;
+        gl!(PixelStorei, gl::PACK_ALIGNMENT, 4)?;
+        gl!(
+            BindFramebuffer,
+            gl::READ_FRAMEBUFFER,
+            previous_framebuffer as u32
+        )?;
+
+        // GL rows start at the bottom, images start at the top.
+        let flipped: Vec<u8> = pixels
+            .chunks_exact(stride)
+            .rev()
+            .flatten()
+            .copied()
+            .collect();
+
+        image::save_buffer(path, &flipped, width, height, image::ColorType::Rgb8)?;
+
+        Ok(())
+    }
+
+    /// Clear the state tracked for the previous batch of events.
+    fn begin_frame(&mut self) {
+        self.resized = false;
+        self.input.begin_frame();
+    }
+
+    /// Translate an SDL event and update the window state tracked from it.
+    ///
+    /// Events without a babo equivalent are skipped.
+    fn handle_event(&mut self, event: &SdlEvent) -> Option<Event> {
+        let event = self
+            .handle_controller_event(event)
+            .or_else(|| translate_event(event))?;
+
+        self.input.handle_event(&event);
+
+        if let Event::Resized { width, height } = event {
+            self.handle_resize(width, height);
+        }
+
+        Some(event)
+    }
+
+    /// Track the new size of the window and cover it with the viewport.
+    fn handle_resize(&mut self, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+        self.resized = true;
+
+        let (width, height) = self.drawable_size();
+        gl_unchecked!(Viewport, 0, 0, width as i32, height as i32);
+    }
 }
 
 impl<'a> WindowTrait<'a> for Window {
-    type Event = Event;
-
     fn viewport(&self) -> (u32, u32) {
         (self.width, self.height)
     }
 
+    fn drawable_size(&self) -> (u32, u32) {
+        self.window.drawable_size()
+    }
+
     fn width(&self) -> u32 {
         self.width
     }
@@ -92,17 +384,194 @@ impl<'a> WindowTrait<'a> for Window {
         self.should_close = true;
     }
 
-    fn events(&'a mut self) -> Vec<Self::Event> {
-        self.event_pump.poll_iter().collect()
+    fn set_fullscreen(&mut self, mode: FullscreenMode) -> Result<(), Error> {
+        let fullscreen = match mode {
+            FullscreenMode::Windowed => FullscreenType::Off,
+            FullscreenMode::Fullscreen => FullscreenType::True,
+            FullscreenMode::Desktop => FullscreenType::Desktop,
+        };
+
+        self.window
+            .set_fullscreen(fullscreen)
+            .map_err(WindowError::from)?;
+
+        let (width, height) = self.window.size();
+        self.handle_resize(width, height);
+
+        Ok(())
+    }
+
+    fn is_fullscreen(&self) -> bool {
+        self.window.fullscreen_state() != FullscreenType::Off
+    }
+
+    fn set_vsync(&mut self, vsync: VSync) -> Result<(), Error> {
+        let video_subsystem = self.window.subsystem();
+
+        let result = match vsync {
+            VSync::Off => video_subsystem.gl_set_swap_interval(SwapInterval::Immediate),
+            VSync::On => video_subsystem.gl_set_swap_interval(SwapInterval::VSync),
+            VSync::Adaptive => video_subsystem
+                .gl_set_swap_interval(SwapInterval::LateSwapTearing)
+                .or_else(|_| video_subsystem.gl_set_swap_interval(SwapInterval::VSync)),
+        };
+
+        Ok(result.map_err(WindowError::from)?)
+    }
+
+    fn vsync(&self) -> VSync {
+        match self.window.subsystem().gl_get_swap_interval() {
+            SwapInterval::Immediate => VSync::Off,
+            SwapInterval::VSync => VSync::On,
+            SwapInterval::LateSwapTearing => VSync::Adaptive,
+        }
+    }
+
+    fn set_depth_test(&mut self, enabled: bool) {
+        if enabled {
+            gl_unchecked!(Enable, gl::DEPTH_TEST);
+        } else {
+            gl_unchecked!(Disable, gl::DEPTH_TEST);
+        }
+
+        self.depth_test = enabled;
+    }
+
+    fn depth_test(&self) -> bool {
+        self.depth_test
+    }
+
+    fn set_depth_func(&mut self, func: DepthFunc) {
+        let gl_func = match func {
+            DepthFunc::Never => gl::NEVER,
+            DepthFunc::Less => gl::LESS,
+            DepthFunc::Equal => gl::EQUAL,
+            DepthFunc::LessEqual => gl::LEQUAL,
+            DepthFunc::Greater => gl::GREATER,
+            DepthFunc::NotEqual => gl::NOTEQUAL,
+            DepthFunc::GreaterEqual => gl::GEQUAL,
+            DepthFunc::Always => gl::ALWAYS,
+        };
+        gl_unchecked!(DepthFunc, gl_func);
+
+        self.depth_func = func;
+    }
+
+    fn depth_func(&self) -> DepthFunc {
+        self.depth_func
+    }
+
+    fn set_cull_face(&mut self, cull_face: Option<CullFace>) {
+        match cull_face {
+            Some(face) => {
+                let gl_face = match face {
+                    CullFace::Back => gl::BACK,
+                    CullFace::Front => gl::FRONT,
+                    CullFace::FrontAndBack => gl::FRONT_AND_BACK,
+                };
+                gl_unchecked!(Enable, gl::CULL_FACE);
+                gl_unchecked!(CullFace, gl_face);
+            }
+            None => gl_unchecked!(Disable, gl::CULL_FACE),
+        }
+
+        self.cull_face = cull_face;
+    }
+
+    fn cull_face(&self) -> Option<CullFace> {
+        self.cull_face
+    }
+
+    fn set_front_face(&mut self, front_face: FrontFace) {
+        let gl_front_face = match front_face {
+            FrontFace::CounterClockwise => gl::CCW,
+            FrontFace::Clockwise => gl::CW,
+        };
+        gl_unchecked!(FrontFace, gl_front_face);
+
+        self.front_face = front_face;
+    }
+
+    fn front_face(&self) -> FrontFace {
+        self.front_face
+    }
+
+    fn set_viewport(&mut self, viewport: Option<(i32, i32, i32, i32)>) {
+        let (drawable_width, drawable_height) = self.drawable_size();
+        let (x, y, width, height) =
+            viewport.unwrap_or((0, 0, drawable_width as i32, drawable_height as i32));
+        gl_unchecked!(Viewport, x, y, width, height);
+    }
+
+    fn set_scissor(&mut self, scissor: Option<(i32, i32, i32, i32)>) {
+        match scissor {
+            Some((x, y, width, height)) => {
+                gl_unchecked!(Enable, gl::SCISSOR_TEST);
+                gl_unchecked!(Scissor, x, y, width, height);
+            }
+            None => gl_unchecked!(Disable, gl::SCISSOR_TEST),
+        }
+    }
+
+    fn events(&'a mut self) -> Vec<Event> {
+        self.poll_events().collect()
+    }
+
+    fn resized(&self) -> bool {
+        self.resized
     }
 
     fn clear(&mut self, r: f32, g: f32, b: f32) {
         gl_unchecked!(ClearColor, r, g, b, 1.0);
         gl_unchecked!(Clear, gl::COLOR_BUFFER_BIT);
         gl_unchecked!(Clear, gl::DEPTH_BUFFER_BIT);
+        gl_unchecked!(Clear, gl::STENCIL_BUFFER_BIT);
+    }
+
+    fn clear_checked(&mut self, r: f32, g: f32, b: f32) -> Result<(), Error> {
+        let status = gl_unchecked!(CheckFramebufferStatus, gl::FRAMEBUFFER);
+        if status != gl::FRAMEBUFFER_COMPLETE {
+            return Err(Error::IncompleteFramebuffer { status });
+        }
+
+        gl_unchecked!(ClearColor, r, g, b, 1.0);
+        gl_unchecked!(
+            Clear,
+            gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT | gl::STENCIL_BUFFER_BIT
+        );
+
+        Ok(check_gl_error("Clear")?)
     }
 
     fn present(&mut self) {
         self.window.gl_swap_window();
+        self.clock.tick();
+    }
+
+    fn delta_time(&self) -> f32 {
+        self.clock.delta()
+    }
+
+    fn fps(&self) -> f32 {
+        self.clock.fps()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rendering::Camera;
+
+    #[test]
+    #[ignore = "needs an SDL video device"]
+    fn camera_from_window_uses_the_drawable_size() {
+        let window = Window::headless(320, 240).unwrap();
+        assert_eq!((window.width(), window.height()), (320, 240));
+
+        let (width, height) = window.drawable_size();
+        let camera = Camera::from(&window);
+        let expected = Camera::new(width as f32, height as f32);
+
+        assert_eq!(camera.projection(), expected.projection());
     }
 }
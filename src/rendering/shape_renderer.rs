@@ -0,0 +1,60 @@
+use nalgebra::{Matrix4, Vector2};
+
+use crate::{
+    error::Error,
+    rendering::{Color, Rect},
+};
+
+/// Draws flat colored primitives in world coordinates, useful for debugging collision and layout.
+pub trait ShapeRendererTrait {
+    fn new() -> Result<Self, Error>
+    where
+        Self: Sized;
+
+    fn fill_rect(
+        &self,
+        projection: &Matrix4<f32>,
+        view: &Matrix4<f32>,
+        rect: &Rect,
+        color: impl Into<Color>,
+    ) -> Result<(), Error>;
+
+    /// Draw the outline of a rectangle, the lines are `thickness` wide on the inside of `rect`.
+    fn stroke_rect(
+        &self,
+        projection: &Matrix4<f32>,
+        view: &Matrix4<f32>,
+        rect: &Rect,
+        thickness: f32,
+        color: impl Into<Color>,
+    ) -> Result<(), Error>;
+
+    fn line(
+        &self,
+        projection: &Matrix4<f32>,
+        view: &Matrix4<f32>,
+        start: Vector2<f32>,
+        end: Vector2<f32>,
+        thickness: f32,
+        color: impl Into<Color>,
+    ) -> Result<(), Error>;
+
+    /// Draw a filled circle.
+    fn circle(
+        &self,
+        projection: &Matrix4<f32>,
+        view: &Matrix4<f32>,
+        center: Vector2<f32>,
+        radius: f32,
+        color: impl Into<Color>,
+    ) -> Result<(), Error>;
+
+    /// Draw a filled convex polygon, the points are in order around the edge.
+    fn polygon(
+        &self,
+        projection: &Matrix4<f32>,
+        view: &Matrix4<f32>,
+        points: &[Vector2<f32>],
+        color: impl Into<Color>,
+    ) -> Result<(), Error>;
+}
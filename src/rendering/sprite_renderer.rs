@@ -1,12 +1,200 @@
-use nalgebra::{Matrix4, Vector2, Vector3};
+use std::rc::Rc;
+
+use nalgebra::{Matrix4, Vector2, Vector3, Vector4};
+
+use crate::{
+    error::Error,
+    rendering::{BlendMode, Color, Rect, TextureAtlas},
+};
+
+/// A single sprite to be drawn as part of a batch.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SpriteInstance {
+    pub position: Vector3<f32>,
+    pub size: Vector2<f32>,
+    pub rotation: f32,
+    /// The normalized point of the sprite to rotate around, `(0.5, 0.5)` is the center.
+    pub origin: Vector2<f32>,
+    pub color: Color,
+}
+
+/// How queued sprites are ordered when they're flushed.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum SortMode {
+    /// Back-to-front, in submission order for equal depths.
+    #[default]
+    Depth,
+    /// Back-to-front, grouping sprites with equal depths by texture.
+    DepthThenTexture,
+    /// Grouped by texture with the fewest draw calls, back-to-front within each texture.
+    ///
+    /// Overlapping transparent sprites with different textures can draw in the wrong order.
+    Texture,
+}
+
+/// Counters from the last `flush`, for profiling.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct FlushStats {
+    /// Sprites sharing a texture next to each other in the sort order are drawn together.
+    pub draw_calls: usize,
+    /// How many times the texture changed between draws.
+    pub texture_binds: usize,
+}
+
+/// Parameters for drawing a single sprite, built up with chained calls.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DrawParams {
+    pub position: Vector3<f32>,
+    /// The size of the sprite, defaults to the size of the texture.
+    pub size: Option<Vector2<f32>>,
+    pub rotation: f32,
+    pub origin: Vector2<f32>,
+    /// The color multiplied with the texture, including the alpha.
+    pub color: Color,
+    /// Mirror the texture horizontally and/or vertically.
+    pub flip: (bool, bool),
+    /// The region of the texture to draw in pixels, defaults to the whole texture.
+    pub source: Option<Rect>,
+}
+
+impl Default for DrawParams {
+    fn default() -> Self {
+        Self {
+            position: Vector3::zeros(),
+            size: None,
+            rotation: 0.0,
+            origin: Vector2::new(0.5, 0.5),
+            color: Color::WHITE,
+            flip: (false, false),
+            source: None,
+        }
+    }
+}
+
+impl DrawParams {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn position(mut self, position: Vector3<f32>) -> Self {
+        self.position = position;
+        self
+    }
+
+    pub fn size(mut self, size: Vector2<f32>) -> Self {
+        self.size = Some(size);
+        self
+    }
+
+    pub fn rotation(mut self, rotation: f32) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn origin(mut self, origin: Vector2<f32>) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    /// Set the color, replacing the alpha as well.
+    pub fn color(mut self, color: impl Into<Color>) -> Self {
+        self.color = color.into();
+        self
+    }
+
+    pub fn alpha(mut self, alpha: f32) -> Self {
+        self.color.a = alpha;
+        self
+    }
+
+    pub fn flip(mut self, horizontal: bool, vertical: bool) -> Self {
+        self.flip = (horizontal, vertical);
+        self
+    }
+
+    pub fn source(mut self, source: Rect) -> Self {
+        self.source = Some(source);
+        self
+    }
+}
 
 pub trait SpriteRendererTrait {
     type Texture;
 
-    fn new() -> Result<Self, Box<dyn std::error::Error>>
+    fn new() -> Result<Self, Error>
     where
         Self: Sized;
 
+    /// Set how sprites are blended with the framebuffer, applies to all following draws.
+    fn set_blend_mode(&mut self, mode: BlendMode);
+    fn blend_mode(&self) -> BlendMode;
+
+    /// Set how queued sprites are ordered by `flush`.
+    fn set_sort_mode(&mut self, mode: SortMode);
+    fn sort_mode(&self) -> SortMode;
+
+    /// The draw call and texture bind counts of the last `flush`.
+    fn flush_stats(&self) -> FlushStats;
+
+    /// Draw a sprite described by `params`.
+    fn draw_params(
+        &self,
+        texture: &Self::Texture,
+        projection: &Matrix4<f32>,
+        view: &Matrix4<f32>,
+        params: &DrawParams,
+    ) -> Result<(), Error>;
+
+    /// Draw a sprite described by `params` with the projection and view already multiplied
+    /// together, like `Camera::view_projection`.
+    fn draw_combined(
+        &self,
+        texture: &Self::Texture,
+        view_projection: &Matrix4<f32>,
+        params: &DrawParams,
+    ) -> Result<(), Error>;
+
+    /// Draw a sprite described by `params` relative to a parent transform, like the world
+    /// matrix of a scene `Node`.
+    fn draw_transformed(
+        &self,
+        texture: &Self::Texture,
+        projection: &Matrix4<f32>,
+        view: &Matrix4<f32>,
+        transform: &Matrix4<f32>,
+        params: &DrawParams,
+    ) -> Result<(), Error>;
+
+    /// Draw a named region of an atlas, the size defaults to the size of the region.
+    fn draw_atlas(
+        &self,
+        atlas: &TextureAtlas<Self::Texture>,
+        name: &str,
+        projection: &Matrix4<f32>,
+        view: &Matrix4<f32>,
+        params: &DrawParams,
+    ) -> Result<(), Error> {
+        let region = atlas.region(name).ok_or_else(|| Error::RegionNotFound {
+            name: name.to_string(),
+        })?;
+
+        let params = DrawParams {
+            size: params
+                .size
+                .or_else(|| Some(Vector2::new(region.width, region.height))),
+            source: Some(region),
+            ..*params
+        };
+
+        self.draw_params(atlas.texture(), projection, view, &params)
+    }
+
+    /// Draw a sprite with its top-left corner at `position`, rotated around `origin`.
+    ///
+    /// The `origin` is normalized to the size, `(0.5, 0.5)` rotates around the center and
+    /// `(0.0, 0.0)` around the top-left corner. The `color` is multiplied with the texture,
+    /// including the alpha.
+    #[allow(clippy::too_many_arguments)]
     fn draw(
         &self,
         texture: &Self::Texture,
@@ -15,6 +203,79 @@ pub trait SpriteRendererTrait {
         position: &Vector3<f32>,
         size: &Vector2<f32>,
         rotation: f32,
-        color: &Vector3<f32>,
-    ) -> Result<(), Box<dyn std::error::Error>>;
+        origin: &Vector2<f32>,
+        color: impl Into<Color>,
+    ) -> Result<(), Error>;
+
+    /// Draw a sprite using only the `source` region of the texture, in pixels.
+    #[allow(clippy::too_many_arguments)]
+    fn draw_region(
+        &self,
+        texture: &Self::Texture,
+        projection: &Matrix4<f32>,
+        view: &Matrix4<f32>,
+        position: &Vector3<f32>,
+        size: &Vector2<f32>,
+        rotation: f32,
+        origin: &Vector2<f32>,
+        color: impl Into<Color>,
+        source: &Rect,
+    ) -> Result<(), Error>;
+
+    /// Draw a texture stretched over `destination` while keeping the borders unstretched.
+    ///
+    /// The `border` insets are `(left, right, top, bottom)` in texels, the corners are drawn
+    /// at their original size, the edges are stretched along one axis and the center along both.
+    fn draw_nine_slice(
+        &self,
+        texture: &Self::Texture,
+        projection: &Matrix4<f32>,
+        view: &Matrix4<f32>,
+        destination: &Rect,
+        border: &Vector4<f32>,
+        color: impl Into<Color>,
+    ) -> Result<(), Error>;
+
+    /// Fill an area by repeating the texture every `tile_size` instead of stretching it.
+    ///
+    /// Fractional tile counts show part of the last tile. Fails if `tile_size` isn't positive,
+    /// or if more than one tile is needed along an axis the texture doesn't repeat on.
+    fn draw_tiled(
+        &self,
+        texture: &Self::Texture,
+        projection: &Matrix4<f32>,
+        view: &Matrix4<f32>,
+        position: &Vector3<f32>,
+        size: &Vector2<f32>,
+        tile_size: &Vector2<f32>,
+    ) -> Result<(), Error>;
+
+    /// Queue a sprite to be drawn on the next `flush`, using `position.z` as the depth.
+    ///
+    /// Use this for transparent sprites, the queue holds on to the texture until it's flushed.
+    #[allow(clippy::too_many_arguments)]
+    fn queue(
+        &mut self,
+        texture: &Rc<Self::Texture>,
+        projection: &Matrix4<f32>,
+        view: &Matrix4<f32>,
+        position: &Vector3<f32>,
+        size: &Vector2<f32>,
+        rotation: f32,
+        origin: &Vector2<f32>,
+        color: impl Into<Color>,
+    );
+
+    /// Draw all queued sprites in the order of the sort mode, by default back-to-front so
+    /// sprites with a higher z are drawn on top.
+    fn flush(&self) -> Result<(), Error>;
+
+    /// Draw many sprites sharing the same texture in a single draw call.
+    fn draw_batch(
+        &self,
+        texture: &Self::Texture,
+        projection: &Matrix4<f32>,
+        view: &Matrix4<f32>,
+        instances: &[SpriteInstance],
+    ) -> Result<(), Error>;
 }
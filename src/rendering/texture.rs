@@ -1,10 +1,126 @@
 #![allow(unused)]
 
+use crate::{error::Error, rendering::Color};
+
+/// How a texture is sampled when drawn smaller or larger than its size.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TextureFilter {
+    /// Use the closest texel, keeps pixel art crisp.
+    Nearest,
+    /// Blend the closest texels.
+    Linear,
+    // Mipmapped filters, only valid when minifying a texture with mipmaps.
+    NearestMipmapNearest,
+    LinearMipmapNearest,
+    NearestMipmapLinear,
+    LinearMipmapLinear,
+}
+
+/// How a texture is sampled outside of the [0, 1] texture coordinate range.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TextureWrap {
+    Repeat,
+    MirroredRepeat,
+    ClampToEdge,
+    ClampToBorder,
+}
+
+/// How a texture is sampled, used when creating a texture.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TextureOptions {
+    pub filter_min: TextureFilter,
+    pub filter_mag: TextureFilter,
+    pub wrap_s: TextureWrap,
+    pub wrap_t: TextureWrap,
+    /// Generate mipmaps, this is implied by a mipmapped `filter_min`.
+    pub mipmaps: bool,
+}
+
+impl Default for TextureOptions {
+    /// Smooth trilinear filtering with mipmaps and repeat wrapping.
+    fn default() -> Self {
+        Self {
+            filter_min: TextureFilter::LinearMipmapLinear,
+            filter_mag: TextureFilter::Linear,
+            wrap_s: TextureWrap::Repeat,
+            wrap_t: TextureWrap::Repeat,
+            mipmaps: true,
+        }
+    }
+}
+
+impl TextureOptions {
+    /// Point sampling without mipmaps, keeps pixel art crisp.
+    pub fn nearest() -> Self {
+        Self {
+            filter_min: TextureFilter::Nearest,
+            filter_mag: TextureFilter::Nearest,
+            mipmaps: false,
+            ..Self::default()
+        }
+    }
+
+    pub fn wrap(self, wrap: TextureWrap) -> Self {
+        Self {
+            wrap_s: wrap,
+            wrap_t: wrap,
+            ..self
+        }
+    }
+}
+
 pub trait TextureTrait {
-    fn from_file(path: &str) -> Result<Self, Box<dyn std::error::Error>>
+    /// Load a texture from an image file with the default options.
+    fn from_file(path: &str) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        Self::from_file_with(path, TextureOptions::default())
+    }
+
+    fn from_file_with(path: &str, options: TextureOptions) -> Result<Self, Error>
+    where
+        Self: Sized;
+
+    /// Create a texture from tightly packed RGBA pixels, with the top row first.
+    fn from_memory(width: u32, height: u32, data: &[u8]) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        Self::from_memory_with(width, height, data, TextureOptions::default())
+    }
+
+    fn from_memory_with(
+        width: u32,
+        height: u32,
+        data: &[u8],
+        options: TextureOptions,
+    ) -> Result<Self, Error>
     where
         Self: Sized;
 
+    /// Create a 1x1 texture of a single color, for drawing flat colored quads.
+    fn solid(color: Color) -> Result<Self, Error>
+    where
+        Self: Sized,
+    {
+        let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+        let pixel = [
+            channel(color.r),
+            channel(color.g),
+            channel(color.b),
+            channel(color.a),
+        ];
+
+        Self::from_memory(1, 1, &pixel)
+    }
+
     fn width(&self) -> u32;
     fn height(&self) -> u32;
+
+    /// Set the minifying and magnifying filters.
+    fn set_filter(&mut self, min: TextureFilter, mag: TextureFilter) -> Result<(), Error>;
+
+    /// Set the horizontal (s) and vertical (t) wrap modes.
+    fn set_wrap(&mut self, s: TextureWrap, t: TextureWrap) -> Result<(), Error>;
 }
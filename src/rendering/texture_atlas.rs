@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::{
+    error::Error,
+    rendering::{Rect, TextureTrait},
+};
+
+/// A single texture containing many named sprites.
+pub struct TextureAtlas<T> {
+    texture: T,
+    regions: HashMap<String, Rect>,
+}
+
+impl<T> TextureAtlas<T> {
+    pub fn new(texture: T) -> Self {
+        Self {
+            texture,
+            regions: HashMap::new(),
+        }
+    }
+
+    /// Create an atlas from a JSON manifest of named regions in pixels.
+    ///
+    /// Both a plain object of `{ "name": { "x", "y", "w", "h" } }` and the TexturePacker
+    /// JSON hash format, with the regions under `"frames"` and `"frame"`, are supported.
+    pub fn from_json(texture: T, json: &str) -> Result<Self, Error> {
+        let manifest: Value = serde_json::from_str(json)?;
+        let frames = manifest.get("frames").unwrap_or(&manifest);
+
+        let frames = frames.as_object().ok_or_else(|| Error::InvalidAtlas {
+            message: "the manifest must be an object of named regions".to_string(),
+        })?;
+
+        let mut atlas = Self::new(texture);
+        for (name, frame) in frames {
+            let frame = frame.get("frame").unwrap_or(frame);
+
+            let field = |key: &str| {
+                frame
+                    .get(key)
+                    .and_then(Value::as_f64)
+                    .map(|value| value as f32)
+                    .ok_or_else(|| Error::InvalidAtlas {
+                        message: format!("region \"{}\" is missing \"{}\"", name, key),
+                    })
+            };
+
+            atlas.add_region(
+                name,
+                Rect::new(field("x")?, field("y")?, field("w")?, field("h")?),
+            );
+        }
+
+        Ok(atlas)
+    }
+
+    pub fn add_region(&mut self, name: &str, region: Rect) {
+        self.regions.insert(name.to_string(), region);
+    }
+
+    pub fn region(&self, name: &str) -> Option<Rect> {
+        self.regions.get(name).copied()
+    }
+
+    pub fn regions(&self) -> impl Iterator<Item = (&str, &Rect)> {
+        self.regions
+            .iter()
+            .map(|(name, region)| (name.as_str(), region))
+    }
+
+    pub fn texture(&self) -> &T {
+        &self.texture
+    }
+}
+
+impl<T: TextureTrait> TextureAtlas<T> {
+    /// Load the atlas texture and its JSON manifest from disk.
+    pub fn from_files(texture_path: &str, manifest_path: &str) -> Result<Self, Error> {
+        let texture = T::from_file(texture_path)?;
+        let manifest = std::fs::read_to_string(manifest_path)?;
+        Self::from_json(texture, &manifest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_json_reads_plain_regions() {
+        let atlas =
+            TextureAtlas::from_json((), r#"{ "player": { "x": 0, "y": 16, "w": 32, "h": 48 } }"#)
+                .unwrap();
+
+        assert_eq!(
+            atlas.region("player"),
+            Some(Rect::new(0.0, 16.0, 32.0, 48.0))
+        );
+        assert_eq!(atlas.region("enemy"), None);
+    }
+
+    #[test]
+    fn from_json_reads_texture_packer_frames() {
+        let atlas = TextureAtlas::from_json(
+            (),
+            r#"{
+                "frames": {
+                    "coin.png": { "frame": { "x": 64, "y": 0, "w": 16, "h": 16 }, "rotated": false }
+                },
+                "meta": { "size": { "w": 128, "h": 128 } }
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            atlas.region("coin.png"),
+            Some(Rect::new(64.0, 0.0, 16.0, 16.0))
+        );
+    }
+
+    #[test]
+    fn from_json_rejects_malformed_manifests() {
+        assert!(matches!(
+            TextureAtlas::from_json((), "{ \"player\": "),
+            Err(Error::Json { .. })
+        ));
+        assert!(matches!(
+            TextureAtlas::from_json((), "[1, 2, 3]"),
+            Err(Error::InvalidAtlas { .. })
+        ));
+        assert!(matches!(
+            TextureAtlas::from_json((), r#"{ "player": { "x": 0, "y": 0, "w": 32 } }"#),
+            Err(Error::InvalidAtlas { .. })
+        ));
+    }
+}
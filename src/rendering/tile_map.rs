@@ -0,0 +1,242 @@
+use nalgebra::{Matrix4, Vector2};
+
+use crate::{
+    error::Error,
+    rendering::{camera::unproject_to_plane, Rect, TextureAtlas},
+};
+
+/// A grid of tiles drawn from the regions of a texture atlas.
+///
+/// Tiles are indices into the tile set, the atlas regions the map was created with.
+/// The top-left tile is at the world origin.
+pub struct TileMap<T> {
+    atlas: TextureAtlas<T>,
+    tile_set: Vec<Rect>,
+    columns: u32,
+    rows: u32,
+    tile_size: Vector2<f32>,
+    tiles: Vec<Option<usize>>,
+}
+
+impl<T> TileMap<T> {
+    /// Create an empty map, `tile_set` names the atlas regions used by each tile index.
+    pub fn new(
+        atlas: TextureAtlas<T>,
+        tile_set: &[&str],
+        columns: u32,
+        rows: u32,
+        tile_size: Vector2<f32>,
+    ) -> Result<Self, Error> {
+        let tile_set = tile_set
+            .iter()
+            .map(|name| {
+                atlas.region(name).ok_or_else(|| Error::RegionNotFound {
+                    name: name.to_string(),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            atlas,
+            tile_set,
+            columns,
+            rows,
+            tile_size,
+            tiles: vec![None; (columns * rows) as usize],
+        })
+    }
+
+    /// The tile at a column and row, `None` if it's empty or outside of the map.
+    pub fn tile(&self, column: u32, row: u32) -> Option<usize> {
+        self.index(column, row).and_then(|index| self.tiles[index])
+    }
+
+    /// Set the tile at a column and row, `None` clears it.
+    pub fn set_tile(&mut self, column: u32, row: u32, tile: Option<usize>) -> Result<(), Error> {
+        let index = self
+            .index(column, row)
+            .ok_or_else(|| Error::InvalidArgument {
+                message: format!(
+                    "tile ({}, {}) is outside of the {}x{} map",
+                    column, row, self.columns, self.rows
+                ),
+            })?;
+
+        if let Some(tile) = tile {
+            if tile >= self.tile_set.len() {
+                return Err(Error::InvalidArgument {
+                    message: format!(
+                        "tile {} is outside of the tile set of {} tiles",
+                        tile,
+                        self.tile_set.len()
+                    ),
+                });
+            }
+        }
+
+        self.tiles[index] = tile;
+
+        Ok(())
+    }
+
+    /// The atlas region of a tile index, in pixels.
+    pub fn tile_region(&self, tile: usize) -> Option<Rect> {
+        self.tile_set.get(tile).copied()
+    }
+
+    /// The world space rectangle covered by the tile at a column and row.
+    pub fn tile_bounds(&self, column: u32, row: u32) -> Rect {
+        Rect::new(
+            column as f32 * self.tile_size.x,
+            row as f32 * self.tile_size.y,
+            self.tile_size.x,
+            self.tile_size.y,
+        )
+    }
+
+    /// The column and row of the tile containing a world space point.
+    pub fn world_to_tile(&self, world: Vector2<f32>) -> Option<(u32, u32)> {
+        let column = (world.x / self.tile_size.x).floor();
+        let row = (world.y / self.tile_size.y).floor();
+
+        if column < 0.0 || row < 0.0 || column >= self.columns as f32 || row >= self.rows as f32 {
+            return None;
+        }
+
+        Some((column as u32, row as u32))
+    }
+
+    /// The range of columns and rows visible through a projection and view.
+    pub fn visible_tiles(
+        &self,
+        projection: &Matrix4<f32>,
+        view: &Matrix4<f32>,
+    ) -> (std::ops::Range<u32>, std::ops::Range<u32>) {
+        let inverse = match (projection * view).try_inverse() {
+            Some(inverse) => inverse,
+            None => return (0..0, 0..0),
+        };
+
+        // Unproject the corners of the screen onto the tile plane to find the visible bounds.
+        let mut min = Vector2::repeat(f32::MAX);
+        let mut max = Vector2::repeat(f32::MIN);
+        for (x, y) in [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)] {
+            let corner = unproject_to_plane(&inverse, Vector2::new(x, y));
+            min = min.inf(&corner);
+            max = max.sup(&corner);
+        }
+
+        let range = |min: f32, max: f32, size: f32, count: u32| {
+            let start = (min / size).floor().clamp(0.0, count as f32) as u32;
+            let end = (max / size).ceil().clamp(0.0, count as f32) as u32;
+            start..end
+        };
+
+        (
+            range(min.x, max.x, self.tile_size.x, self.columns),
+            range(min.y, max.y, self.tile_size.y, self.rows),
+        )
+    }
+
+    pub fn columns(&self) -> u32 {
+        self.columns
+    }
+
+    pub fn rows(&self) -> u32 {
+        self.rows
+    }
+
+    pub fn tile_size(&self) -> Vector2<f32> {
+        self.tile_size
+    }
+
+    pub fn atlas(&self) -> &TextureAtlas<T> {
+        &self.atlas
+    }
+
+    fn index(&self, column: u32, row: u32) -> Option<usize> {
+        if column < self.columns && row < self.rows {
+            Some((row * self.columns + column) as usize)
+        } else {
+            None
+        }
+    }
+}
+
+/// Draws a tile map, keeping the tiles on the GPU between frames.
+pub trait TileMapRendererTrait {
+    type Texture;
+
+    /// Take ownership of a map and upload all of its tiles.
+    fn new(map: TileMap<Self::Texture>) -> Result<Self, Error>
+    where
+        Self: Sized;
+
+    fn map(&self) -> &TileMap<Self::Texture>;
+
+    /// Set a tile in the map, only updating that tile on the GPU.
+    fn set_tile(&mut self, column: u32, row: u32, tile: Option<usize>) -> Result<(), Error>;
+
+    /// Draw the tiles visible through the projection and view in a single draw call.
+    fn draw(&self, projection: &Matrix4<f32>, view: &Matrix4<f32>) -> Result<(), Error>;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rendering::Camera;
+
+    fn map() -> TileMap<()> {
+        let mut atlas = TextureAtlas::new(());
+        atlas.add_region("grass", Rect::new(0.0, 0.0, 16.0, 16.0));
+
+        TileMap::new(atlas, &["grass"], 100, 100, Vector2::new(16.0, 16.0)).unwrap()
+    }
+
+    fn visible(camera: &Camera) -> (std::ops::Range<u32>, std::ops::Range<u32>) {
+        map().visible_tiles(camera.projection(), camera.view())
+    }
+
+    #[test]
+    fn visible_tiles_covers_the_screen() {
+        let mut camera = Camera::new(320.0, 240.0);
+
+        // Looking at (0, 0) to (320, 240), tile edges line up with the screen edges.
+        camera.set_position(Vector2::new(160.0, 120.0));
+        assert_eq!(visible(&camera), (0..20, 0..15));
+
+        // Half a tile further, the partially visible tiles on the far edges are included.
+        camera.set_position(Vector2::new(168.0, 128.0));
+        assert_eq!(visible(&camera), (0..21, 0..16));
+
+        camera.set_y_down(false);
+        assert_eq!(visible(&camera), (0..21, 0..16));
+    }
+
+    #[test]
+    fn visible_tiles_follows_the_zoom() {
+        let mut camera = Camera::new(320.0, 240.0);
+        camera.set_position(Vector2::new(164.0, 126.0));
+        camera.set_zoom(Vector2::new(2.0, 2.0));
+
+        // Showing (84, 66) to (244, 186).
+        assert_eq!(visible(&camera), (5..16, 4..12));
+    }
+
+    #[test]
+    fn visible_tiles_under_perspective_matches_the_tile_plane() {
+        let mut camera =
+            Camera::perspective(320.0, 240.0, std::f32::consts::FRAC_PI_4, 1.0, 1000.0);
+        camera.set_position(Vector2::new(168.0, 128.0));
+
+        assert_eq!(visible(&camera), (0..21, 0..16));
+    }
+
+    #[test]
+    fn visible_tiles_is_empty_outside_of_the_map() {
+        let mut camera = Camera::new(320.0, 240.0);
+        camera.set_position(Vector2::new(-1000.0, -1000.0));
+
+        assert_eq!(visible(&camera), (0..0, 0..0));
+    }
+}
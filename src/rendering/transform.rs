@@ -0,0 +1,108 @@
+use nalgebra::{Matrix4, Vector2, Vector3};
+
+/// A 2D position, rotation and scale, applied in the order scale, rotate, translate.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Transform2D {
+    pub position: Vector2<f32>,
+    /// The rotation in radians.
+    pub rotation: f32,
+    pub scale: Vector2<f32>,
+}
+
+impl Default for Transform2D {
+    fn default() -> Self {
+        Self {
+            position: Vector2::zeros(),
+            rotation: 0.0,
+            scale: Vector2::new(1.0, 1.0),
+        }
+    }
+}
+
+impl Transform2D {
+    pub fn new(position: Vector2<f32>, rotation: f32, scale: Vector2<f32>) -> Self {
+        Self {
+            position,
+            rotation,
+            scale,
+        }
+    }
+
+    /// The transform as a matrix, relative to whatever it's attached to.
+    pub fn matrix(&self) -> Matrix4<f32> {
+        Matrix4::new_translation(&Vector3::new(self.position.x, self.position.y, 0.0))
+            * Matrix4::new_rotation(Vector3::z() * self.rotation)
+            * Matrix4::new_nonuniform_scaling(&Vector3::new(self.scale.x, self.scale.y, 1.0))
+    }
+}
+
+/// A node in a scene graph, its transform is relative to its parent.
+///
+/// Holds a `value` to draw or look up for the node, like a texture or a sprite index.
+/// Pass a world matrix to `SpriteRendererTrait::draw_transformed` to draw relative to it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Node<T> {
+    pub transform: Transform2D,
+    pub value: T,
+    children: Vec<Node<T>>,
+}
+
+impl<T> Node<T> {
+    pub fn new(transform: Transform2D, value: T) -> Self {
+        Self {
+            transform,
+            value,
+            children: Vec::new(),
+        }
+    }
+
+    /// Attach a child, returning its index in `children`.
+    pub fn add_child(&mut self, child: Node<T>) -> usize {
+        self.children.push(child);
+        self.children.len() - 1
+    }
+
+    /// Detach and return the child at `index`, shifting the following children down.
+    pub fn remove_child(&mut self, index: usize) -> Option<Node<T>> {
+        (index < self.children.len()).then(|| self.children.remove(index))
+    }
+
+    pub fn child(&self, index: usize) -> Option<&Node<T>> {
+        self.children.get(index)
+    }
+
+    pub fn child_mut(&mut self, index: usize) -> Option<&mut Node<T>> {
+        self.children.get_mut(index)
+    }
+
+    pub fn children(&self) -> &[Node<T>] {
+        &self.children
+    }
+
+    pub fn children_mut(&mut self) -> &mut [Node<T>] {
+        &mut self.children
+    }
+
+    /// The transform relative to the parent.
+    pub fn local_matrix(&self) -> Matrix4<f32> {
+        self.transform.matrix()
+    }
+
+    /// The transform in world space, given the world matrix of the parent.
+    ///
+    /// Use the identity for the root node.
+    pub fn world_matrix(&self, parent: &Matrix4<f32>) -> Matrix4<f32> {
+        parent * self.local_matrix()
+    }
+
+    /// Visit this node and all of its descendants depth-first with their world matrices,
+    /// parents before their children.
+    pub fn visit(&self, parent: &Matrix4<f32>, visitor: &mut impl FnMut(&Node<T>, &Matrix4<f32>)) {
+        let world = self.world_matrix(parent);
+        visitor(self, &world);
+
+        for child in &self.children {
+            child.visit(&world, visitor);
+        }
+    }
+}
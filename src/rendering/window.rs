@@ -1,14 +1,153 @@
-pub trait WindowTrait<'a> {
-    type Event;
+use crate::{error::Error, rendering::Event};
+
+/// How a window covers the screen.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum FullscreenMode {
+    #[default]
+    Windowed,
+    /// Exclusive fullscreen, changing the display mode to the window size.
+    Fullscreen,
+    /// Borderless fullscreen at the desktop resolution.
+    Desktop,
+}
+
+/// When presenting waits for the display to refresh.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum VSync {
+    /// Present immediately, lowest latency but can tear.
+    Off,
+    #[default]
+    On,
+    /// Wait for the refresh unless the frame is late, then present immediately.
+    Adaptive,
+}
+
+/// When a fragment passes the depth test, comparing its depth to the stored depth.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum DepthFunc {
+    Never,
+    /// Closer fragments win, sprites with a higher z are drawn on top.
+    #[default]
+    Less,
+    Equal,
+    LessEqual,
+    Greater,
+    NotEqual,
+    GreaterEqual,
+    Always,
+}
+
+/// Which faces of triangles are skipped while culling is enabled.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum CullFace {
+    #[default]
+    Back,
+    Front,
+    FrontAndBack,
+}
+
+/// The winding order of the vertices of a front facing triangle, as seen on screen.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum FrontFace {
+    #[default]
+    CounterClockwise,
+    Clockwise,
+}
+
+/// Settings chosen when a window and its context are created.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct WindowOptions {
+    /// The number of MSAA samples per pixel, 0 or 1 disables multisampling.
+    pub samples: u8,
+}
+
+impl WindowOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn samples(mut self, samples: u8) -> Self {
+        self.samples = samples;
+        self
+    }
+}
 
+pub trait WindowTrait<'a> {
     fn viewport(&self) -> (u32, u32);
     fn width(&self) -> u32;
     fn height(&self) -> u32;
+
+    /// The size of the window in pixels, larger than the logical size on high-DPI displays.
+    ///
+    /// Viewports, scissors and cameras are in drawable pixels.
+    fn drawable_size(&self) -> (u32, u32);
+
+    /// The number of drawable pixels per logical pixel, multiply mouse positions by this to
+    /// get drawable pixels.
+    fn scale_factor(&self) -> f32 {
+        match self.width() {
+            0 => 1.0,
+            width => self.drawable_size().0 as f32 / width as f32,
+        }
+    }
     fn set_title(&mut self, title: &str);
     fn running(&self) -> bool;
     fn stop(&mut self);
 
-    fn events(&'a mut self) -> Vec<Self::Event>;
+    /// Switch between windowed and fullscreen, the viewport and size are updated like a resize.
+    fn set_fullscreen(&mut self, mode: FullscreenMode) -> Result<(), Error>;
+    fn is_fullscreen(&self) -> bool;
+
+    /// Set the vsync mode, `Adaptive` falls back to `On` when it isn't supported.
+    fn set_vsync(&mut self, vsync: VSync) -> Result<(), Error>;
+    fn vsync(&self) -> VSync;
+
+    /// Enable depth testing so sprites with a higher `position.z` hide the ones behind them,
+    /// whatever order they're drawn in. With the default camera z has to be within [-1, 1].
+    ///
+    /// Transparent pixels still write depth and hide what is drawn after them. Draw opaque
+    /// sprites first with depth testing, then queue the transparent ones and `flush` them
+    /// back-to-front.
+    fn set_depth_test(&mut self, enabled: bool);
+    fn depth_test(&self) -> bool;
+
+    /// Set the depth comparison used while depth testing is enabled.
+    fn set_depth_func(&mut self, func: DepthFunc);
+    fn depth_func(&self) -> DepthFunc;
+
+    /// Skip drawing the triangles facing away, `None` (the default) draws both sides.
+    ///
+    /// The Y-down camera mirrors the screen, which flips the winding of everything drawn
+    /// through it.
+    fn set_cull_face(&mut self, cull_face: Option<CullFace>);
+    fn cull_face(&self) -> Option<CullFace>;
+
+    /// Set which winding order counts as front facing.
+    fn set_front_face(&mut self, front_face: FrontFace);
+    fn front_face(&self) -> FrontFace;
+
+    /// Restrict rendering to a sub-rectangle of the window, `None` covers the full window.
+    fn set_viewport(&mut self, viewport: Option<(i32, i32, i32, i32)>);
+
+    /// Clip drawing to a rectangle in drawable pixels with a bottom-left origin, `None` disables
+    /// clipping.
+    fn set_scissor(&mut self, scissor: Option<(i32, i32, i32, i32)>);
+
+    fn events(&'a mut self) -> Vec<Event>;
+
+    /// Whether the window was resized by the last call to `events`, the viewport is already
+    /// updated but cameras need a `Camera::set_screen`.
+    fn resized(&self) -> bool;
     fn clear(&mut self, r: f32, g: f32, b: f32);
+
+    /// Like `clear`, but always checks that the bound framebuffer is complete and reports GL
+    /// errors, even in release builds.
+    fn clear_checked(&mut self, r: f32, g: f32, b: f32) -> Result<(), Error>;
     fn present(&mut self);
+
+    /// The seconds between the last two calls to `present`.
+    fn delta_time(&self) -> f32;
+
+    /// The frames per second, averaged over the recent frames.
+    fn fps(&self) -> f32;
 }
@@ -34,18 +34,43 @@ pub fn gl_error_string(error: u32) -> &'static str {
     }
 }
 
+/// Check `glGetError` even when `gl!` doesn't, reporting any error as coming from `method`.
+///
+/// The error may have been raised by any call since the error was last checked.
+pub fn check_gl_error(method: &str) -> Result<(), GlError> {
+    match unsafe { gl::GetError() } {
+        gl::NO_ERROR => Ok(()),
+        code => Err(GlError {
+            method: method.to_string(),
+            code,
+            message: gl_error_string(code).to_string(),
+        }),
+    }
+}
+
+/// Whether `gl!` checks `glGetError` after each call.
+///
+/// Checking forces the driver to synchronize on every call, which is slow in hot loops, so it
+/// is only done in debug builds or with the `gl-checks` feature. Without it `gl!` behaves like
+/// `gl_unchecked!` and always returns `Ok`, errors go unnoticed until a debug build.
+#[doc(hidden)]
+#[inline(always)]
+pub const fn gl_checks_enabled() -> bool {
+    cfg!(any(debug_assertions, feature = "gl-checks"))
+}
+
 #[macro_export]
-/// Call an OpenGL function and check for errors.
+/// Call an OpenGL function and check for errors, see `gl_checks_enabled`.
 macro_rules! gl {
     ( $func:tt, $($arg:tt)* ) => {{
         use $crate::utils::{GlError, gl_error_string};
 
         unsafe {
             let result = gl::$func($($arg)*);
-            let err = gl::GetError();
+            let err = if $crate::utils::gl_checks_enabled() { gl::GetError() } else { gl::NO_ERROR };
             if err != gl::NO_ERROR {
                 let reason = gl_error_string(err);
-                Err(GlError { method: "$func".to_string(), code: err, message: reason.to_string() })
+                Err(GlError { method: stringify!($func).to_string(), code: err, message: reason.to_string() })
             } else {
                 Ok(result)
             }
@@ -56,10 +81,10 @@ macro_rules! gl {
 
         unsafe {
             let result = gl::$func();
-            let err = gl::GetError();
+            let err = if $crate::utils::gl_checks_enabled() { gl::GetError() } else { gl::NO_ERROR };
             if err != gl::NO_ERROR {
                 let reason = gl_error_string(err);
-                Err(GlError { method: "$func".to_string(), code: err, message: reason.to_string() })
+                Err(GlError { method: stringify!($func).to_string(), code: err, message: reason.to_string() })
             } else {
                 Ok(result)
             }